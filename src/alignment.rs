@@ -0,0 +1,500 @@
+use raylib::prelude::*;
+use std::fmt::Write as _;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+
+/// Segundos de simulación que cubre la lista: cinco años terrestres
+pub const SEARCH_HORIZON: f64 = 600.0;
+/// Ancho del sector para el desfile de planetas
+pub const PARADE_ANGLE: f32 = 30.0;
+/// Mínimo de planetas juntos para que cuente como desfile
+pub const PARADE_PLANETS: usize = 4;
+/// Archivo al que se exporta la lista, en el directorio de trabajo
+pub const CSV_PATH: &str = "alineaciones.csv";
+/// Paso del muestreo; el planeta más rápido avanza 4° por segundo
+const SAMPLE_STEP: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentKind {
+    /// Dos cuerpos (o un planeta y la estrella) en la misma longitud eclíptica
+    Conjunction,
+    /// Un planeta en el lado opuesto del cielo a la estrella
+    Opposition,
+    /// Máxima separación angular de un planeta interior respecto a la
+    /// estrella, hacia el este (tarde) o el oeste (madrugada)
+    GreatestElongation { east: bool },
+    /// Varios planetas dentro de un mismo sector del cielo
+    Parade,
+}
+
+/// Alineación vista desde `observer`. `angle` es, en grados, la
+/// separación en conjunciones, la distancia al punto opuesto a la estrella
+/// en oposiciones, la elongación en elongaciones y el ancho del sector en
+/// desfiles.
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    pub kind: AlignmentKind,
+    pub time: f64,
+    pub observer: BodyId,
+    pub bodies: Vec<BodyId>,
+    pub angle: f32,
+}
+
+impl Alignment {
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            AlignmentKind::Conjunction => "Conjuncion",
+            AlignmentKind::Opposition => "Oposicion",
+            AlignmentKind::GreatestElongation { east: true } => "Max. elongacion este",
+            AlignmentKind::GreatestElongation { east: false } => "Max. elongacion oeste",
+            AlignmentKind::Parade => "Desfile",
+        }
+    }
+
+    pub fn names(&self, bodies: &BodyRegistry) -> String {
+        let names: Vec<&str> = self.bodies.iter().map(|&id| bodies[id].name.as_str()).collect();
+        names.join(" - ")
+    }
+
+    pub fn describe(&self, bodies: &BodyRegistry) -> String {
+        format!("{} {} ({:.1} grados)", self.label(), self.names(bodies), self.angle)
+    }
+
+    /// Cámara junto a la superficie del observador mirando hacia el centro
+    /// del grupo, con los cuerpos en su posición del momento. Devuelve
+    /// (posición, objetivo).
+    pub fn viewpoint(&self, bodies: &BodyRegistry, safe_distance: f32) -> (DVec3, DVec3) {
+        let observer = &bodies[self.observer];
+        let mut sum = Vector3::zero();
+        for &id in &self.bodies {
+            sum += (bodies[id].position - observer.position).to_f32().normalized();
+        }
+        let direction = if sum.length() > 1e-4 { sum.normalized() } else { Vector3::new(1.0, 0.0, 0.0) };
+        let position = observer.position + direction * (observer.radius + safe_distance + 0.5);
+        (position, position + direction * 10.0)
+    }
+}
+
+/// Parámetros de una búsqueda
+#[derive(Debug, Clone, Copy)]
+pub struct AlignmentQuery {
+    pub observer: BodyId,
+    pub from: f64,
+    pub to: f64,
+    /// Separación máxima de una conjunción y ancho del sector de un desfile
+    pub max_angle: f32,
+    pub parade_planets: usize,
+}
+
+impl AlignmentQuery {
+    pub fn new(observer: BodyId, from: f64, to: f64) -> Self {
+        Self {
+            observer,
+            from,
+            to,
+            max_angle: PARADE_ANGLE,
+            parade_planets: PARADE_PLANETS,
+        }
+    }
+}
+
+/// Lo que se ve desde el observador en un instante del muestreo
+struct Sky {
+    time: f64,
+    star: Vector3,
+    planets: Vec<Vector3>,
+}
+
+/// Longitud eclíptica en grados de una dirección. Crece en el sentido en
+/// que avanzan las órbitas con velocidad positiva.
+fn longitude(direction: Vector3) -> f32 {
+    direction.z.atan2(direction.x).to_degrees()
+}
+
+/// Diferencia de ángulos llevada a [-180, 180)
+fn wrap(degrees: f32) -> f32 {
+    (degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn separation(a: Vector3, b: Vector3) -> f32 {
+    a.cross(b).length().atan2(a.dot(b)).to_degrees()
+}
+
+/// Busca alineaciones de los planetas de la estrella principal vistas desde
+/// `query.observer`, muestreando los rieles desde el estado actual (`now`).
+/// Los instantes de conjunciones y oposiciones se interpolan entre
+/// muestras y los de las elongaciones con una parábola. Resultado ordenado
+/// por tiempo.
+pub fn find_alignments(bodies: &BodyRegistry, now: f64, query: &AlignmentQuery) -> Vec<Alignment> {
+    let observer = query.observer;
+    let Some(star) = bodies.star_of(observer).filter(|&s| s != observer) else {
+        return Vec::new();
+    };
+    let planets: Vec<BodyId> = bodies
+        .planets()
+        .into_iter()
+        .filter(|&id| id != observer && bodies[id].active)
+        .collect();
+    // Planetas interiores: los únicos con máxima elongación
+    let inferior: Vec<bool> = planets
+        .iter()
+        .map(|&id| bodies[id].orbit.semi_major_axis < bodies[observer].orbit.semi_major_axis)
+        .collect();
+
+    let sky_at = |time: f64| {
+        let positions = bodies.rails_positions(time - now);
+        let eye = positions[observer];
+        Sky {
+            time,
+            star: (positions[star] - eye).to_f32().normalized(),
+            planets: planets.iter().map(|&id| (positions[id] - eye).to_f32().normalized()).collect(),
+        }
+    };
+
+    let mut found = Vec::new();
+    let mut parade: Option<Alignment> = None;
+    let mut before: Option<Sky> = None;
+    let mut previous: Option<Sky> = None;
+    let steps = ((query.to - query.from) / SAMPLE_STEP).ceil().max(0.0) as usize;
+
+    for step in 0..=steps {
+        let sky = sky_at((query.from + step as f64 * SAMPLE_STEP).min(query.to));
+
+        if let Some(last) = &previous {
+            // Conjunciones entre planetas y con la estrella
+            for i in 0..planets.len() {
+                let pairs = (i + 1..planets.len()).map(|j| (Some(j), sky.planets[j], last.planets[j]));
+                let with_star = std::iter::once((None, sky.star, last.star));
+                for (j, now_dir, last_dir) in pairs.chain(with_star) {
+                    let passed = crossing(
+                        wrap(longitude(last.planets[i]) - longitude(last_dir)),
+                        wrap(longitude(sky.planets[i]) - longitude(now_dir)),
+                    );
+                    let Some(f) = passed else { continue };
+                    let angle = separation(
+                        last.planets[i].lerp(sky.planets[i], f),
+                        last_dir.lerp(now_dir, f),
+                    );
+                    if angle <= query.max_angle {
+                        found.push(Alignment {
+                            kind: AlignmentKind::Conjunction,
+                            time: last.time + (sky.time - last.time) * f as f64,
+                            observer,
+                            bodies: vec![planets[i], j.map_or(star, |j| planets[j])],
+                            angle,
+                        });
+                    }
+                }
+
+                // Oposición: la longitud relativa a la estrella cruza 180°
+                let opposite = crossing(
+                    wrap(longitude(last.planets[i]) - longitude(last.star) - 180.0),
+                    wrap(longitude(sky.planets[i]) - longitude(sky.star) - 180.0),
+                );
+                if let Some(f) = opposite.filter(|_| !inferior[i]) {
+                    found.push(Alignment {
+                        kind: AlignmentKind::Opposition,
+                        time: last.time + (sky.time - last.time) * f as f64,
+                        observer,
+                        bodies: vec![planets[i]],
+                        angle: separation(last.planets[i].lerp(sky.planets[i], f), -last.star.lerp(sky.star, f)),
+                    });
+                }
+
+                // Máxima elongación: máximo local de la separación con la estrella
+                if let Some(first) = before.as_ref().filter(|_| inferior[i]) {
+                    let e0 = separation(first.planets[i], first.star);
+                    let e1 = separation(last.planets[i], last.star);
+                    let e2 = separation(sky.planets[i], sky.star);
+                    if e1 > e0 && e1 >= e2 {
+                        let curvature = e0 - 2.0 * e1 + e2;
+                        let offset = if curvature.abs() > 1e-6 { 0.5 * (e0 - e2) / curvature } else { 0.0 };
+                        let east = wrap(longitude(last.planets[i]) - longitude(last.star)) > 0.0;
+                        found.push(Alignment {
+                            kind: AlignmentKind::GreatestElongation { east },
+                            time: last.time + SAMPLE_STEP * offset as f64,
+                            observer,
+                            bodies: vec![planets[i]],
+                            angle: e1,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Desfile: se sigue el mejor grupo mientras haya suficientes planetas
+        // juntos y se informa el instante más apretado
+        match parade_at(&sky, &planets, query.max_angle).filter(|(group, _)| group.len() >= query.parade_planets) {
+            Some((group, arc)) => {
+                let better = parade.as_ref().is_none_or(|p| {
+                    group.len() > p.bodies.len() || (group.len() == p.bodies.len() && arc < p.angle)
+                });
+                if better {
+                    parade = Some(Alignment {
+                        kind: AlignmentKind::Parade,
+                        time: sky.time,
+                        observer,
+                        bodies: group,
+                        angle: arc,
+                    });
+                }
+            }
+            None => found.extend(parade.take()),
+        }
+
+        before = previous.replace(sky);
+    }
+    found.extend(parade);
+
+    found.retain(|a| a.time >= query.from && a.time <= query.to);
+    found.sort_by(|a, b| a.time.total_cmp(&b.time));
+    found
+}
+
+/// Fracción del paso en la que una diferencia angular pasa por cero. Los
+/// saltos de +180 a -180 no cuentan como cruce.
+fn crossing(before: f32, after: f32) -> Option<f32> {
+    let changed = (before < 0.0) != (after < 0.0);
+    (changed && before.abs() < 90.0 && after.abs() < 90.0).then(|| before / (before - after))
+}
+
+/// Grupo más grande de planetas dentro de un sector de `max_angle` grados y
+/// el ancho que ocupan
+fn parade_at(sky: &Sky, planets: &[BodyId], max_angle: f32) -> Option<(Vec<BodyId>, f32)> {
+    let mut by_longitude: Vec<(f32, BodyId)> = sky
+        .planets
+        .iter()
+        .zip(planets)
+        .map(|(&dir, &id)| (longitude(dir), id))
+        .collect();
+    by_longitude.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut best: Option<(Vec<BodyId>, f32)> = None;
+    for start in 0..by_longitude.len() {
+        let first = by_longitude[start].0;
+        let mut group = Vec::new();
+        let mut arc = 0.0;
+        for k in 0..by_longitude.len() {
+            let (lon, id) = by_longitude[(start + k) % by_longitude.len()];
+            let offset = (lon - first).rem_euclid(360.0);
+            if offset > max_angle {
+                break;
+            }
+            group.push(id);
+            arc = offset;
+        }
+        let better = best.as_ref().is_none_or(|(g, a)| group.len() > g.len() || (group.len() == g.len() && arc < *a));
+        if better {
+            best = Some((group, arc));
+        }
+    }
+    best
+}
+
+/// Lista navegable de alineaciones desde un planeta, con exportación a CSV
+pub struct AlignmentBrowser {
+    pub open: bool,
+    pub observer: Option<BodyId>,
+    pub results: Vec<Alignment>,
+    pub selected: usize,
+}
+
+impl AlignmentBrowser {
+    /// El observador por defecto es la Tierra si existe, si no el primer planeta
+    pub fn new(bodies: &BodyRegistry) -> Self {
+        let observer = bodies.find("Tierra").or_else(|| bodies.planets().first().copied());
+        Self {
+            open: false,
+            observer,
+            results: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Recalcula la lista desde `now` hasta `SEARCH_HORIZON` más adelante
+    pub fn search(&mut self, bodies: &BodyRegistry, now: f64) {
+        self.results = match self.observer {
+            Some(observer) => find_alignments(bodies, now, &AlignmentQuery::new(observer, now, now + SEARCH_HORIZON)),
+            None => Vec::new(),
+        };
+        self.selected = 0;
+    }
+
+    /// Pasa al siguiente planeta como observador y repite la búsqueda
+    pub fn next_observer(&mut self, bodies: &BodyRegistry, now: f64) {
+        let planets = bodies.planets();
+        let current = self.observer.and_then(|o| planets.iter().position(|&p| p == o));
+        self.observer = current.map_or(planets.first(), |i| planets.get((i + 1) % planets.len())).copied();
+        self.search(bodies, now);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.results.len().saturating_sub(1));
+    }
+
+    pub fn current(&self) -> Option<&Alignment> {
+        self.results.get(self.selected)
+    }
+
+    /// CSV con una fila por alineación. `date` convierte un instante del
+    /// reloj en fecha cuando hay efemérides.
+    pub fn to_csv(&self, bodies: &BodyRegistry, date: impl Fn(f64) -> Option<String>) -> String {
+        let mut csv = String::from("tiempo_sim,fecha,tipo,cuerpos,angulo_grados,observador\n");
+        for alignment in &self.results {
+            let _ = writeln!(
+                csv,
+                "{:.2},{},{},{},{:.2},{}",
+                alignment.time,
+                date(alignment.time).unwrap_or_default(),
+                alignment.label(),
+                alignment.names(bodies),
+                alignment.angle,
+                bodies[alignment.observer].name,
+            );
+        }
+        csv
+    }
+
+    /// Panel a la derecha con las alineaciones alrededor de la seleccionada
+    pub fn draw(&self, d: &mut RaylibDrawHandle, bodies: &BodyRegistry, time_label: impl Fn(f64) -> String) {
+        if !self.open {
+            return;
+        }
+        const ROWS: usize = 14;
+        let (x, y, width) = (d.get_screen_width() - 520, 90, 510);
+        d.draw_rectangle(x, y, width, 70 + ROWS as i32 * 20, Color::new(0, 0, 0, 190));
+        d.draw_rectangle_lines(x, y, width, 70 + ROWS as i32 * 20, Color::SKYBLUE);
+
+        let observer = self.observer.map_or("-", |o| bodies[o].name.as_str());
+        d.draw_text(
+            &format!("Alineaciones desde {} ({})", observer, self.results.len()),
+            x + 10,
+            y + 8,
+            18,
+            Color::SKYBLUE,
+        );
+        d.draw_text("Arriba/Abajo  Enter: ir  Q: observador  Y: CSV", x + 10, y + 32, 14, Color::LIGHTGRAY);
+
+        let first = self.selected.saturating_sub(ROWS / 2).min(self.results.len().saturating_sub(ROWS));
+        for (row, (i, alignment)) in self.results.iter().enumerate().skip(first).take(ROWS).enumerate() {
+            let color = if i == self.selected { Color::YELLOW } else { Color::WHITE };
+            d.draw_text(
+                &format!("{}  {}", time_label(alignment.time), alignment.describe(bodies)),
+                x + 10,
+                y + 56 + row as i32 * 20,
+                14,
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+    use crate::orbital_elements::OrbitalElements;
+
+    fn planet(name: &str, a: f32, speed: f32, start: f32) -> CelestialBody {
+        let orbit = OrbitalElements::new(a, 0.0, 0.0, 0.0, 0.0, start);
+        CelestialBody::new_planet(name, 1.0, Color::WHITE, orbit, speed, 0.0)
+    }
+
+    /// Sol, un planeta interior (15 u, 8°/s), el observador (30 u, 3°/s,
+    /// desde 0°) y un exterior (60 u, 1°/s, desde 90°)
+    fn system() -> (BodyRegistry, [BodyId; 4]) {
+        let mut bodies = BodyRegistry::new();
+        let sun = bodies.insert(CelestialBody::new_sun("Sol", 5.0, Color::WHITE));
+        let inner = bodies.insert(planet("Interior", 15.0, 8.0, 0.0));
+        let observer = bodies.insert(planet("Observador", 30.0, 3.0, 0.0));
+        let outer = bodies.insert(planet("Exterior", 60.0, 1.0, 90.0));
+        (bodies, [sun, inner, observer, outer])
+    }
+
+    fn direction(longitude: f32) -> Vector3 {
+        let (sin, cos) = longitude.to_radians().sin_cos();
+        Vector3::new(cos, 0.0, sin)
+    }
+
+    #[test]
+    fn wrap_en_el_borde_de_180() {
+        assert_eq!(wrap(0.0), 0.0);
+        assert_eq!(wrap(190.0), -170.0);
+        assert_eq!(wrap(-190.0), 170.0);
+        assert_eq!(wrap(359.0), -1.0);
+        // 180 y -180 son el mismo ángulo; queda del lado cerrado
+        assert_eq!(wrap(180.0), -180.0);
+        assert_eq!(wrap(-180.0), -180.0);
+        assert_eq!(wrap(540.0), -180.0);
+    }
+
+    #[test]
+    fn crossing_ignora_el_salto_de_180() {
+        assert_eq!(crossing(-10.0, 10.0), Some(0.5));
+        assert_eq!(crossing(10.0, -30.0), Some(0.25));
+        assert_eq!(crossing(5.0, 8.0), None);
+        // De +179 a -179 la diferencia dio la vuelta, no pasó por cero
+        assert_eq!(crossing(179.0, -179.0), None);
+        assert_eq!(crossing(-170.0, 175.0), None);
+    }
+
+    #[test]
+    fn longitud_crece_con_las_orbitas() {
+        assert!((longitude(direction(30.0)) - 30.0).abs() < 1e-4);
+        assert!((longitude(direction(200.0)) + 160.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn desfile_que_cruza_el_borde_de_180() {
+        let (_, [extra, inner, observer, outer]) = system();
+        let sky = Sky {
+            time: 0.0,
+            star: direction(0.0),
+            planets: [170.0, -170.0, -165.0, 60.0].map(direction).to_vec(),
+        };
+        let (group, arc) = parade_at(&sky, &[inner, observer, outer, extra], 30.0).unwrap();
+        assert_eq!(group, vec![inner, observer, outer]);
+        assert!((arc - 25.0).abs() < 1e-3, "{}", arc);
+    }
+
+    #[test]
+    fn conjuncion_oposicion_y_elongaciones() {
+        let (bodies, [sun, inner, observer, outer]) = system();
+        let found = find_alignments(&bodies, 0.0, &AlignmentQuery::new(observer, 0.0, 140.0));
+        let times = |kind: AlignmentKind, body: BodyId| -> Vec<f64> {
+            found.iter().filter(|a| a.kind == kind && a.bodies.contains(&body)).map(|a| a.time).collect()
+        };
+        let close = |times: Vec<f64>, expected: &[f64]| {
+            assert_eq!(times.len(), expected.len(), "{:?} y no {:?}", times, expected);
+            for (time, expected) in times.iter().zip(expected) {
+                assert!((time - expected).abs() < 0.05, "{} y no {}", time, expected);
+            }
+        };
+
+        // El observador alcanza al exterior (2°/s relativos desde 90°):
+        // oposición a los 45 s y conjunción con el Sol a los 135 s
+        close(times(AlignmentKind::Opposition, outer), &[45.0]);
+        let with_sun: Vec<f64> = found
+            .iter()
+            .filter(|a| a.kind == AlignmentKind::Conjunction && a.bodies == [outer, sun])
+            .map(|a| a.time)
+            .collect();
+        close(with_sun, &[135.0]);
+
+        // El interior (5°/s relativos) alcanza la máxima elongación, 30°,
+        // a 60° y a 300° del observador: al oeste a los 12 s y al este a los 60 s
+        close(times(AlignmentKind::GreatestElongation { east: false }, inner), &[12.0, 84.0]);
+        close(times(AlignmentKind::GreatestElongation { east: true }, inner), &[60.0, 132.0]);
+        for elongation in found.iter().filter(|a| matches!(a.kind, AlignmentKind::GreatestElongation { .. })) {
+            assert!((elongation.angle - 30.0).abs() < 0.1, "{}", elongation.angle);
+        }
+        // Un interior nunca está en oposición
+        assert!(times(AlignmentKind::Opposition, inner).is_empty());
+        assert!(found.windows(2).all(|w| w[0].time <= w[1].time));
+    }
+}
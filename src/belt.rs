@@ -0,0 +1,226 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+use serde::Deserialize;
+
+use crate::floating_origin::DVec3;
+use crate::orbital_elements::OrbitalElements;
+use crate::physics::GRAVITATIONAL_CONSTANT;
+
+/// Parámetros de un cinturón de cuerpos menores. La misma configuración
+/// sirve para el cinturón de asteroides y para el de Kuiper.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BeltSettings {
+    pub name: String,
+    /// Misma semilla, mismo cinturón
+    #[serde(default)]
+    pub seed: u64,
+    pub count: usize,
+    /// Rango (mínimo, máximo) del semieje mayor
+    pub semi_major_axis: (f32, f32),
+    /// Dispersión de la excentricidad (distribución de Rayleigh)
+    #[serde(default)]
+    pub eccentricity_sigma: f32,
+    /// Dispersión de la inclinación en grados (distribución de Rayleigh)
+    #[serde(default)]
+    pub inclination_sigma: f32,
+    /// Rango (mínimo, máximo) del radio de cada roca
+    pub radius: (f32, f32),
+    /// Exponente q de la ley de potencias dN/dr ∝ r^-q; 3.5 es el valor de
+    /// una población en equilibrio colisional
+    #[serde(default = "default_size_exponent")]
+    pub size_exponent: f32,
+    pub color: (u8, u8, u8, u8),
+}
+
+fn default_size_exponent() -> f32 {
+    3.5
+}
+
+struct Rock {
+    orbit: OrbitalElements,
+    mean_anomaly: f32,
+    /// Grados por segundo de simulación
+    mean_motion: f32,
+    radius: f32,
+    /// Semieje y radio generados, en la escala artística
+    base_semi_major_axis: f32,
+    base_radius: f32,
+}
+
+/// Cinturón generado: cada roca sigue su propia órbita kepleriana alrededor
+/// de la estrella principal, con el mismo modelo que `CelestialBody`.
+pub struct Belt {
+    pub name: String,
+    pub color: Color,
+    rocks: Vec<Rock>,
+    /// Posición de cada roca relativa a la estrella, en `f64` como la de
+    /// los cuerpos
+    positions: Vec<DVec3>,
+}
+
+impl Belt {
+    /// Genera las rocas. El movimiento medio sale de la tercera ley de Kepler
+    /// con la masa de la estrella, igual que en el modo N-cuerpos.
+    pub fn generate(settings: &BeltSettings, primary_mass: f32) -> Self {
+        let mut rng = StdRng::seed_from_u64(settings.seed);
+        let (a_min, a_max) = settings.semi_major_axis;
+        let mu = GRAVITATIONAL_CONSTANT * primary_mass;
+
+        let rocks = (0..settings.count)
+            .map(|_| {
+                let a = rng.random_range(a_min..=a_max);
+                let e = rayleigh(&mut rng, settings.eccentricity_sigma);
+                let i = rayleigh(&mut rng, settings.inclination_sigma).min(90.0);
+                let orbit = OrbitalElements::new(
+                    a,
+                    e,
+                    i,
+                    rng.random_range(0.0..360.0),
+                    rng.random_range(0.0..360.0),
+                    rng.random_range(0.0..360.0),
+                );
+                let radius = power_law(&mut rng, settings.radius, settings.size_exponent);
+                Rock {
+                    orbit,
+                    mean_anomaly: orbit.mean_anomaly_epoch,
+                    mean_motion: (mu / (a * a * a)).sqrt().to_degrees(),
+                    radius,
+                    base_semi_major_axis: a,
+                    base_radius: radius,
+                }
+            })
+            .collect();
+
+        let (r, g, b, alpha) = settings.color;
+        let mut belt = Self {
+            name: settings.name.clone(),
+            color: Color::new(r, g, b, alpha),
+            rocks,
+            positions: Vec::with_capacity(settings.count),
+        };
+        belt.update(0.0);
+        belt
+    }
+
+    pub fn len(&self) -> usize {
+        self.rocks.len()
+    }
+
+    /// Cambia de escala: cada roca pasa a la distancia `distance(a)` de su
+    /// semieje artístico y su radio se multiplica por `size`. El movimiento
+    /// medio no cambia, como en los cuerpos.
+    pub fn rescale(&mut self, distance: impl Fn(f32) -> f32, size: f32) {
+        for rock in &mut self.rocks {
+            rock.orbit.semi_major_axis = distance(rock.base_semi_major_axis);
+            rock.radius = rock.base_radius * size;
+        }
+        self.update(0.0);
+    }
+
+    /// Avanza las rocas y recalcula sus posiciones
+    pub fn update(&mut self, delta_time: f32) {
+        self.positions.clear();
+        for rock in &mut self.rocks {
+            rock.mean_anomaly = (rock.mean_anomaly + rock.mean_motion * delta_time).rem_euclid(360.0);
+            self.positions.push(rock.orbit.precise_position_at(rock.mean_anomaly as f64));
+        }
+    }
+}
+
+fn rayleigh(rng: &mut StdRng, sigma: f32) -> f32 {
+    if sigma <= 0.0 {
+        return 0.0;
+    }
+    let u: f32 = rng.random();
+    sigma * (-2.0 * (1.0 - u).ln()).sqrt()
+}
+
+/// Muestra un radio con densidad ∝ r^-q en [min, max] invirtiendo la CDF
+fn power_law(rng: &mut StdRng, (min, max): (f32, f32), q: f32) -> f32 {
+    let u: f32 = rng.random();
+    if (q - 1.0).abs() < 1e-3 {
+        return min * (max / min).powf(u);
+    }
+    let k = 1.0 - q;
+    let (lo, hi) = (min.powf(k), max.powf(k));
+    (lo + (hi - lo) * u).powf(1.0 / k)
+}
+
+const INSTANCING_VS: &str = r#"#version 330
+in vec3 vertexPosition;
+in vec3 vertexNormal;
+in mat4 instanceTransform;
+uniform mat4 mvp;
+uniform vec3 starPosition;
+out float shade;
+void main() {
+    vec4 world = instanceTransform * vec4(vertexPosition, 1.0);
+    vec3 normal = normalize(mat3(instanceTransform) * vertexNormal);
+    vec3 to_sun = normalize(starPosition - world.xyz);
+    shade = 0.25 + 0.75 * max(dot(normal, to_sun), 0.0);
+    gl_Position = mvp * world;
+}
+"#;
+
+const INSTANCING_FS: &str = r#"#version 330
+in float shade;
+uniform vec4 colDiffuse;
+out vec4 finalColor;
+void main() {
+    finalColor = vec4(colDiffuse.rgb * shade, colDiffuse.a);
+}
+"#;
+
+/// Dibuja los cinturones con una sola llamada instanciada por cinturón.
+/// Las matrices de instancia se arman cada frame relativas a la cámara
+/// (estrella + roca - cámara, en `f64`), como el resto de la escena: en la
+/// escala real el cinturón de Kuiper está a decenas de miles de unidades y
+/// en `f32` las rocas temblarían. La luz viene de la estrella principal,
+/// que en N-cuerpos se mueve.
+pub struct BeltRenderer {
+    mesh: Mesh,
+    material: WeakMaterial,
+    star_position_loc: i32,
+    transforms: Vec<Matrix>,
+}
+
+impl BeltRenderer {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        let mut shader = rl.load_shader_from_memory(thread, Some(INSTANCING_VS), Some(INSTANCING_FS));
+        let mvp = shader.get_shader_location("mvp");
+        let instance = shader.get_shader_location_attribute("instanceTransform");
+        let star_position_loc = shader.get_shader_location("starPosition");
+        shader.locs_mut()[ShaderLocationIndex::SHADER_LOC_MATRIX_MVP as usize] = mvp;
+        shader.locs_mut()[ShaderLocationIndex::SHADER_LOC_MATRIX_MODEL as usize] = instance;
+
+        let mut material = rl.load_material_default(thread);
+        // El material se queda con el shader durante toda la ejecución
+        *material.shader_mut() = unsafe { shader.make_weak() };
+
+        Self {
+            mesh: Mesh::gen_mesh_sphere(thread, 1.0, 4, 6),
+            material,
+            star_position_loc,
+            transforms: Vec::new(),
+        }
+    }
+
+    pub fn draw<D: RaylibDraw3D>(&mut self, d: &mut D, belt: &Belt, star: DVec3, origin: DVec3) {
+        if belt.positions.is_empty() {
+            return;
+        }
+        self.transforms.clear();
+        for (rock, &position) in belt.rocks.iter().zip(&belt.positions) {
+            let pos = (star + position).relative_to(origin);
+            self.transforms.push(
+                Matrix::scale(rock.radius, rock.radius, rock.radius) * Matrix::translate(pos.x, pos.y, pos.z),
+            );
+        }
+        self.material
+            .shader_mut()
+            .set_shader_value(self.star_position_loc, star.relative_to(origin));
+        self.material.maps_mut()[MaterialMapIndex::MATERIAL_MAP_ALBEDO as usize].color = belt.color.into();
+        d.draw_mesh_instanced(&self.mesh, self.material.clone(), &self.transforms);
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+use crate::celestial_body::CelestialBody;
+use crate::floating_origin::DVec3;
+use crate::shader::BodyType;
+
+/// Identificador estable de un cuerpo. Los cuerpos nunca se eliminan del
+/// registro (X solo los desactiva), así que el id no cambia durante la
+/// ejecución aunque se agreguen cuerpos o cambie el árbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BodyId(u32);
+
+impl BodyId {
+    fn slot(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Registro de cuerpos con búsqueda por nombre y árbol padre/hijos.
+/// `update_order` recorre el árbol en orden topológico (cada padre antes que
+/// sus hijos), así que el orden en que se insertan los cuerpos no importa.
+pub struct BodyRegistry {
+    bodies: Vec<CelestialBody>,
+    names: HashMap<String, BodyId>,
+    children: Vec<Vec<BodyId>>,
+    update_order: Vec<BodyId>,
+}
+
+impl BodyRegistry {
+    pub fn new() -> Self {
+        Self {
+            bodies: Vec::new(),
+            names: HashMap::new(),
+            children: Vec::new(),
+            update_order: Vec::new(),
+        }
+    }
+
+    /// Agrega un cuerpo sin padre. Los nombres deben ser únicos.
+    pub fn insert(&mut self, mut body: CelestialBody) -> BodyId {
+        assert!(
+            !self.names.contains_key(&body.name),
+            "Ya existe un cuerpo llamado '{}'",
+            body.name
+        );
+        let id = BodyId(self.bodies.len() as u32);
+        body.parent = None;
+        self.names.insert(body.name.clone(), id);
+        self.bodies.push(body);
+        self.children.push(Vec::new());
+        self.rebuild_order();
+        id
+    }
+
+    /// Cambia el padre de un cuerpo. Falla si el cambio formaría un ciclo.
+    pub fn set_parent(&mut self, child: BodyId, parent: Option<BodyId>) -> Result<(), String> {
+        let cycle = parent.filter(|&p| p == child || self.ancestors(p).any(|a| a == child));
+        if let Some(p) = cycle {
+            return Err(format!(
+                "'{}' no puede orbitar a '{}': se formaría un ciclo",
+                self[child].name, self[p].name
+            ));
+        }
+
+        if let Some(old) = self[child].parent {
+            self.children[old.slot()].retain(|&c| c != child);
+        }
+        if let Some(p) = parent {
+            self.children[p.slot()].push(child);
+        }
+        self.bodies[child.slot()].parent = parent;
+        self.rebuild_order();
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<BodyId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = BodyId> + use<> {
+        (0..self.bodies.len() as u32).map(BodyId)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, CelestialBody> {
+        self.bodies.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, CelestialBody> {
+        self.bodies.iter_mut()
+    }
+
+    /// Cuerpos en orden de id, para los sistemas que trabajan sobre arreglos
+    /// paralelos (integrador, efemérides)
+    pub fn as_mut_slice(&mut self) -> &mut [CelestialBody] {
+        &mut self.bodies
+    }
+
+    pub fn children(&self, id: BodyId) -> &[BodyId] {
+        &self.children[id.slot()]
+    }
+
+    /// Padre, abuelo, ... hasta la raíz
+    pub fn ancestors(&self, id: BodyId) -> impl Iterator<Item = BodyId> + '_ {
+        std::iter::successors(self[id].parent, move |&p| self[p].parent)
+    }
+
+    /// Estrella principal: la primera estrella sin padre. Los cuerpos sin
+    /// padre orbitan el origen, donde está esta estrella.
+    pub fn primary(&self) -> Option<BodyId> {
+        self.ids().find(|&id| self[id].is_sun && self[id].parent.is_none())
+    }
+
+    /// Estrella alrededor de la cual gira un cuerpo: la primera estrella
+    /// entre sus ancestros o, si no hay, la principal
+    pub fn star_of(&self, id: BodyId) -> Option<BodyId> {
+        self.ancestors(id).find(|&a| self[a].is_sun).or(self.primary())
+    }
+
+    /// Planetas de la estrella principal: los cuerpos sin padre que no son
+    /// estrellas ni cometas y luego los hijos directos de ella, en el orden
+    /// del escenario
+    pub fn planets(&self) -> Vec<BodyId> {
+        let mut planets: Vec<BodyId> = self
+            .ids()
+            .filter(|&id| self[id].parent.is_none() && !self[id].is_sun)
+            .collect();
+        if let Some(primary) = self.primary() {
+            planets.extend_from_slice(self.children(primary));
+        }
+        planets.retain(|&id| self[id].body_type != BodyType::Comet);
+        planets
+    }
+
+    /// Orden topológico: cada padre aparece antes que sus hijos
+    pub fn update_order(&self) -> &[BodyId] {
+        &self.update_order
+    }
+
+    /// Ruta desde la raíz, p. ej. "Sol / Tierra / Luna"
+    pub fn path(&self, id: BodyId) -> String {
+        let mut names: Vec<&str> = self.ancestors(id).map(|a| self[a].name.as_str()).collect();
+        names.reverse();
+        names.push(&self[id].name);
+        names.join(" / ")
+    }
+
+    /// Cuerpo activo más cercano a un punto que cumple el filtro
+    pub fn nearest(&self, point: DVec3, filter: impl Fn(&CelestialBody) -> bool) -> Option<BodyId> {
+        self.ids()
+            .filter(|&id| self[id].active && filter(&self[id]))
+            .min_by(|&a, &b| {
+                let da = (self[a].position - point).length();
+                let db = (self[b].position - point).length();
+                da.total_cmp(&db)
+            })
+    }
+
+    /// Avanza los rieles recorriendo el árbol de padres a hijos
+    pub fn update_rails(&mut self, delta_time: f32) {
+        for k in 0..self.update_order.len() {
+            let id = self.update_order[k];
+            let parent_pos = self[id].parent.map(|p| self[p].position);
+            if let Some(p) = self[id].parent.filter(|_| self[id].equatorial_orbit) {
+                self.bodies[id.slot()].orbit_frame = self[p].equator_rotation();
+            }
+            self.bodies[id.slot()].update(delta_time, parent_pos);
+        }
+    }
+
+    /// Posiciones que tendrían los cuerpos sobre los rieles `delta_time`
+    /// segundos de simulación después del instante actual (antes si es
+    /// negativo), sin moverlos
+    pub fn rails_positions(&self, delta_time: f64) -> RailsPositions {
+        let mut positions: Vec<DVec3> = self.bodies.iter().map(|b| b.position).collect();
+        for &id in &self.update_order {
+            let body = &self[id];
+            if body.is_sun {
+                continue;
+            }
+            let base_pos = body.parent.map_or(DVec3::zero(), |p| positions[p.slot()]);
+            let angle = body.orbital_angle + body.orbital_speed as f64 * delta_time;
+            positions[id.slot()] = base_pos + body.orbit.precise_position_at(angle).rotate_by(body.orbit_frame);
+        }
+        RailsPositions(positions)
+    }
+
+    fn rebuild_order(&mut self) {
+        let mut order = Vec::with_capacity(self.bodies.len());
+        let mut stack: Vec<BodyId> = self.ids().filter(|&id| self[id].parent.is_none()).collect();
+        stack.reverse();
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.children[id.slot()].iter().rev().copied());
+        }
+        self.update_order = order;
+    }
+}
+
+/// Posiciones de todos los cuerpos en otro instante, indexadas por id
+pub struct RailsPositions(Vec<DVec3>);
+
+impl Index<BodyId> for RailsPositions {
+    type Output = DVec3;
+
+    fn index(&self, id: BodyId) -> &DVec3 {
+        &self.0[id.slot()]
+    }
+}
+
+impl Index<BodyId> for BodyRegistry {
+    type Output = CelestialBody;
+
+    fn index(&self, id: BodyId) -> &CelestialBody {
+        &self.bodies[id.slot()]
+    }
+}
+
+impl IndexMut<BodyId> for BodyRegistry {
+    fn index_mut(&mut self, id: BodyId) -> &mut CelestialBody {
+        &mut self.bodies[id.slot()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbital_elements::OrbitalElements;
+    use raylib::prelude::Color;
+
+    fn body(name: &str) -> CelestialBody {
+        CelestialBody::new_planet(name, 1.0, Color::WHITE, OrbitalElements::circular(10.0), 1.0, 1.0)
+    }
+
+    fn position(registry: &BodyRegistry, id: BodyId) -> usize {
+        registry.update_order().iter().position(|&b| b == id).unwrap()
+    }
+
+    #[test]
+    fn padres_antes_que_hijos() {
+        let mut registry = BodyRegistry::new();
+        // Se insertan al revés: la luna antes que su planeta y este antes que el Sol
+        let moon = registry.insert(body("Luna"));
+        let planet = registry.insert(body("Tierra"));
+        let sun = registry.insert(body("Sol"));
+        registry.set_parent(moon, Some(planet)).unwrap();
+        registry.set_parent(planet, Some(sun)).unwrap();
+
+        assert_eq!(registry.update_order().len(), 3);
+        assert!(position(&registry, sun) < position(&registry, planet));
+        assert!(position(&registry, planet) < position(&registry, moon));
+        assert_eq!(registry.children(sun), &[planet]);
+        assert_eq!(registry.ancestors(moon).collect::<Vec<_>>(), vec![planet, sun]);
+    }
+
+    #[test]
+    fn cambiar_de_padre_actualiza_el_arbol() {
+        let mut registry = BodyRegistry::new();
+        let a = registry.insert(body("A"));
+        let b = registry.insert(body("B"));
+        let moon = registry.insert(body("Luna"));
+        registry.set_parent(moon, Some(a)).unwrap();
+        registry.set_parent(moon, Some(b)).unwrap();
+
+        assert!(registry.children(a).is_empty());
+        assert_eq!(registry.children(b), &[moon]);
+        assert!(position(&registry, b) < position(&registry, moon));
+    }
+
+    #[test]
+    fn rechaza_ciclos() {
+        let mut registry = BodyRegistry::new();
+        let a = registry.insert(body("A"));
+        let b = registry.insert(body("B"));
+        let c = registry.insert(body("C"));
+        registry.set_parent(b, Some(a)).unwrap();
+        registry.set_parent(c, Some(b)).unwrap();
+
+        assert!(registry.set_parent(a, Some(a)).is_err());
+        assert!(registry.set_parent(a, Some(c)).is_err());
+        // Un intento fallido no toca el árbol
+        assert_eq!(registry[a].parent, None);
+        assert_eq!(registry.update_order().len(), 3);
+        assert!(position(&registry, a) < position(&registry, c));
+    }
+}
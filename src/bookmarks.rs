@@ -0,0 +1,105 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::body_registry::BodyRegistry;
+use crate::camera::CameraController;
+use crate::floating_origin::DVec3;
+use crate::warp_path::WarpRequest;
+
+/// Archivo de los marcadores, en el directorio de trabajo
+pub const BOOKMARKS_PATH: &str = "marcadores.ron";
+
+/// Pose guardada de la cámara
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BookmarkPose {
+    /// Posición y punto mirado en coordenadas del mundo
+    Fixed { position: (f64, f64, f64), target: (f64, f64, f64) },
+    /// Desvío cámara - cuerpo en radios del cuerpo, así sirve en cualquier
+    /// escala; al volver se mira al cuerpo donde esté. El cuerpo va por
+    /// nombre porque los `BodyId` no duran entre sesiones.
+    Body { body: String, radii: (f32, f32, f32) },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub pose: BookmarkPose,
+    pub fovy: f32,
+    /// Escala activa al guardar; las poses fijas solo coinciden en ella
+    pub scale: String,
+}
+
+impl Bookmark {
+    /// Pose actual; si la cámara orbita un cuerpo se guarda relativa a él
+    pub fn capture(camera: &CameraController, bodies: &BodyRegistry, scale: &str) -> Self {
+        let pose = match camera.tracking {
+            Some(id) => {
+                let radii = (camera.position - bodies[id].position).to_f32() / bodies[id].radius;
+                BookmarkPose::Body { body: bodies[id].name.clone(), radii: (radii.x, radii.y, radii.z) }
+            }
+            None => BookmarkPose::Fixed {
+                position: (camera.position.x, camera.position.y, camera.position.z),
+                target: (camera.target.x, camera.target.y, camera.target.z),
+            },
+        };
+        Self { pose, fovy: camera.fovy, scale: scale.to_string() }
+    }
+
+    /// Warp a la pose guardada; `None` si el cuerpo ya no está
+    pub fn warp_request(&self, name: &str, key: KeyboardKey, bodies: &BodyRegistry) -> Option<WarpRequest> {
+        let label = format!("marcador {}", name);
+        match &self.pose {
+            &BookmarkPose::Fixed { position: (px, py, pz), target: (tx, ty, tz) } => {
+                let position = DVec3::new(px, py, pz);
+                let offset = (position - DVec3::new(tx, ty, tz)).to_f32();
+                Some(WarpRequest { key, label, body: None, point: position, offset, fovy: Some(self.fovy) })
+            }
+            BookmarkPose::Body { body, radii: (x, y, z) } => {
+                let id = bodies.find(body).filter(|&id| bodies[id].active)?;
+                let offset = Vector3::new(*x, *y, *z) * bodies[id].radius;
+                Some(WarpRequest { key, label, body: Some(id), point: bodies[id].position, offset, fovy: Some(self.fovy) })
+            }
+        }
+    }
+}
+
+/// Marcadores por nombre; los de las teclas rápidas se llaman "1" a "9"
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Bookmarks {
+    bookmarks: BTreeMap<String, Bookmark>,
+}
+
+impl Bookmarks {
+    /// Sin archivo no hay marcadores; uno inválido se avisa y se ignora
+    /// (se reescribe al guardar el próximo)
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match ron::from_str(&text) {
+            Ok(bookmarks) => bookmarks,
+            Err(err) => {
+                println!("⚠️  '{}' no tiene marcadores válidos: {}", path, err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|err| err.to_string())?;
+        std::fs::write(path, text).map_err(|err| err.to_string())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.get(name)
+    }
+
+    /// Guarda o reemplaza
+    pub fn set(&mut self, name: &str, bookmark: Bookmark) {
+        self.bookmarks.insert(name.to_string(), bookmark);
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.bookmarks.keys().map(String::as_str)
+    }
+}
@@ -1,35 +1,127 @@
 use raylib::prelude::*;
 
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+use crate::warp_path::WarpPath;
+
+const WORLD_UP: Vector3 = Vector3::new(0.0, 1.0, 0.0);
+/// Límite del cabeceo en el modo eclíptico, para no pasar por la vertical
+const MAX_PITCH: f32 = 89.0;
+/// Factor de distancia por paso de la rueda al orbitar un cuerpo
+const ZOOM_STEP: f32 = 0.9;
+/// Teclas de desplazamiento (WASD, Espacio y Shift)
+const MOVE_KEYS: [KeyboardKey; 6] = [
+    KeyboardKey::KEY_W,
+    KeyboardKey::KEY_A,
+    KeyboardKey::KEY_S,
+    KeyboardKey::KEY_D,
+    KeyboardKey::KEY_SPACE,
+    KeyboardKey::KEY_LEFT_SHIFT,
+];
+/// Avance del warp por segundo de pantalla (dura 0.5 s)
+const WARP_RATE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Horizonte fijo: W/S avanzan sobre el plano eclíptico, el giro es
+    /// alrededor de Y y el cabeceo no pasa de la vertical
+    Ecliptic,
+    /// Vuelo libre con pitch, yaw y roll; W/S avanzan hacia donde se mira
+    Free,
+}
+
+impl CameraMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            CameraMode::Ecliptic => "ecliptica",
+            CameraMode::Free => "libre",
+        }
+    }
+}
+
+/// Cámara en coordenadas del mundo (doble precisión). raylib solo ve la
+/// cámara de `render_camera`, ubicada en el origen: la escena se dibuja
+/// relativa a `position`.
 pub struct CameraController {
-    pub camera: Camera3D,
+    pub position: DVec3,
+    pub target: DVec3,
+    pub up: Vector3,
+    pub fovy: f32,
+    pub mode: CameraMode,
+    /// Con el mouse capturado su movimiento gira la vista
+    pub mouse_captured: bool,
+    /// Grados por píxel de movimiento del mouse
+    pub mouse_sensitivity: f32,
     pub move_speed: f32,
     pub rotation_speed: f32,
-    pub target_position: Option<Vector3>,
+    /// Cuerpo alrededor del cual orbita la cámara (arcball) y al que
+    /// acompaña; WASD, Espacio o Shift lo sueltan
+    pub tracking: Option<BodyId>,
+    /// Cámara - cuerpo seguido; se gira al orbitar y se escala con la rueda
+    track_offset: Vector3,
+    /// Al terminar un warp a un cuerpo, quedarse orbitándolo para que no se
+    /// vaya del cuadro
+    pub co_move: bool,
+    /// Cuerpo al que va el warp en curso; su destino se corrige cada frame
+    warp_body: Option<BodyId>,
+    pub target_position: Option<DVec3>,
     pub warp_progress: f32,
     pub is_warping: bool,
-    pub start_warp_pos: Vector3,
+    /// Camino del warp en curso, que rodea los cuerpos (ver `WarpPath`)
+    warp_path: WarpPath,
     warp_offset: Vector3,
-    warp_target_body_pos: Vector3,
+    warp_target_body_pos: DVec3,
+    /// Campo de visión al empezar y al terminar el warp
+    warp_fovy: (f32, f32),
 }
 
 impl CameraController {
-    pub fn new(position: Vector3) -> Self {
-        let target = Vector3::new(0.0, 0.0, 0.0);
+    pub fn new(position: DVec3) -> Self {
+        let target = DVec3::zero();
         Self {
-            camera: Camera3D::perspective(
-                position,
-                target,
-                Vector3::new(0.0, 1.0, 0.0),
-                60.0,
-            ),
+            position,
+            target,
+            up: Vector3::new(0.0, 1.0, 0.0),
+            fovy: 60.0,
+            mode: CameraMode::Ecliptic,
+            mouse_captured: false,
+            mouse_sensitivity: 0.15,
             move_speed: 10.0,
             rotation_speed: 50.0,
+            tracking: None,
+            track_offset: Vector3::zero(),
+            co_move: true,
+            warp_body: None,
             target_position: None,
             warp_progress: 0.0,
             is_warping: false,
-            start_warp_pos: position,
-            warp_offset: target - position,
-            warp_target_body_pos: Vector3::zero(),
+            warp_path: WarpPath::straight(position, position),
+            warp_offset: (target - position).to_f32(),
+            warp_target_body_pos: DVec3::zero(),
+            warp_fovy: (60.0, 60.0),
+        }
+    }
+
+    /// Cámara para raylib: en el origen, mirando en la misma dirección
+    pub fn render_camera(&self) -> Camera3D {
+        let forward = self.forward();
+        Camera3D::perspective(Vector3::zero(), self.target.relative_to(self.position), orthonormal_up(forward, self.up), self.fovy)
+    }
+
+    /// Dirección de la vista, normalizada
+    pub fn forward(&self) -> Vector3 {
+        (self.target - self.position).to_f32().normalized()
+    }
+
+    /// Cambia entre el modo eclíptico y el libre; al volver al eclíptico
+    /// se nivela el horizonte
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Ecliptic => CameraMode::Free,
+            CameraMode::Free => CameraMode::Ecliptic,
+        };
+        if self.mode == CameraMode::Ecliptic {
+            self.up = WORLD_UP;
         }
     }
 
@@ -38,110 +130,323 @@ impl CameraController {
             self.update_warp(delta_time);
             return;
         }
+        // Con Alt el teclado es de los marcadores: Alt + Shift + número
+        // guarda uno y no debe mover la cámara
+        let alt = rl.is_key_down(KeyboardKey::KEY_LEFT_ALT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_ALT);
+        // Moverse suelta el cuerpo seguido: tras un warp que acompaña al
+        // planeta se sigue volando como siempre
+        if self.tracking.is_some() && !alt && MOVE_KEYS.iter().any(|&key| rl.is_key_down(key)) {
+            self.tracking = None;
+        }
+        if self.tracking.is_some() {
+            self.update_orbit(rl, delta_time);
+            return;
+        }
+
+        // Flechas y mouse: giro (yaw), cabeceo (pitch) y, con el botón
+        // derecho en modo libre, alabeo (roll); todo en grados
+        let (mut yaw, mut pitch, mut roll) = (0.0, 0.0, 0.0);
+        if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+            yaw -= self.rotation_speed * delta_time;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+            yaw += self.rotation_speed * delta_time;
+        }
+        if self.mouse_captured {
+            let delta = rl.get_mouse_delta() * self.mouse_sensitivity;
+            if self.mode == CameraMode::Free && rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT) {
+                roll += delta.x;
+            } else {
+                yaw += delta.x;
+                pitch -= delta.y;
+            }
+        }
+        self.rotate_view(yaw, pitch, roll);
+        if alt {
+            return;
+        }
 
-        let forward = (self.camera.target - self.camera.position).normalized();
-        let right = forward.cross(self.camera.up).normalized();
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalized();
+        let step = self.move_speed * delta_time;
+        // En el modo eclíptico WASD mueve en horizontal y Espacio/Shift por
+        // Y; en el libre, sobre los ejes de la vista
+        let (ahead, side, lift) = match self.mode {
+            CameraMode::Ecliptic => (Vector3::new(forward.x, 0.0, forward.z), Vector3::new(right.x, 0.0, right.z), WORLD_UP),
+            CameraMode::Free => (forward, right, self.up),
+        };
 
-        // WASD: movimiento horizontal
+        let mut motion = Vector3::zero();
         if rl.is_key_down(KeyboardKey::KEY_W) {
-            self.camera.position.x += forward.x * self.move_speed * delta_time;
-            self.camera.position.z += forward.z * self.move_speed * delta_time;
-            self.camera.target.x += forward.x * self.move_speed * delta_time;
-            self.camera.target.z += forward.z * self.move_speed * delta_time;
+            motion += ahead * step;
         }
         if rl.is_key_down(KeyboardKey::KEY_S) {
-            self.camera.position.x -= forward.x * self.move_speed * delta_time;
-            self.camera.position.z -= forward.z * self.move_speed * delta_time;
-            self.camera.target.x -= forward.x * self.move_speed * delta_time;
-            self.camera.target.z -= forward.z * self.move_speed * delta_time;
+            motion -= ahead * step;
         }
         if rl.is_key_down(KeyboardKey::KEY_A) {
-            self.camera.position.x -= right.x * self.move_speed * delta_time;
-            self.camera.position.z -= right.z * self.move_speed * delta_time;
-            self.camera.target.x -= right.x * self.move_speed * delta_time;
-            self.camera.target.z -= right.z * self.move_speed * delta_time;
+            motion -= side * step;
         }
         if rl.is_key_down(KeyboardKey::KEY_D) {
-            self.camera.position.x += right.x * self.move_speed * delta_time;
-            self.camera.position.z += right.z * self.move_speed * delta_time;
-            self.camera.target.x += right.x * self.move_speed * delta_time;
-            self.camera.target.z += right.z * self.move_speed * delta_time;
+            motion += side * step;
         }
-
-        // Espacio/Shift: arriba/abajo
         if rl.is_key_down(KeyboardKey::KEY_SPACE) {
-            self.camera.position.y += self.move_speed * delta_time;
-            self.camera.target.y += self.move_speed * delta_time;
+            motion += lift * step;
         }
         if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
-            self.camera.position.y -= self.move_speed * delta_time;
-            self.camera.target.y -= self.move_speed * delta_time;
+            motion -= lift * step;
+        }
+        self.position += motion;
+        self.target += motion;
+    }
+
+    /// Gira la vista con cuaterniones alrededor de los ejes de la cámara:
+    /// yaw positivo mira a la derecha, pitch positivo hacia arriba y roll
+    /// positivo inclina el horizonte a la derecha. En el modo eclíptico el
+    /// yaw es alrededor de Y, no hay roll y el pitch se limita.
+    fn rotate_view(&mut self, yaw: f32, mut pitch: f32, roll: f32) {
+        if yaw == 0.0 && pitch == 0.0 && roll == 0.0 {
+            return;
+        }
+        let offset = (self.target - self.position).to_f32();
+        let distance = offset.length();
+        if distance <= 0.0 {
+            return;
+        }
+        let mut forward = offset / distance;
+        let mut up = orthonormal_up(forward, self.up);
+        let right = forward.cross(up).normalized();
+
+        let rotations = match self.mode {
+            CameraMode::Ecliptic => {
+                if pitch != 0.0 {
+                    let current = forward.y.clamp(-1.0, 1.0).asin().to_degrees();
+                    pitch = (current + pitch).clamp(-MAX_PITCH, MAX_PITCH) - current;
+                }
+                let right = forward.cross(WORLD_UP).normalized();
+                [
+                    Quaternion::from_axis_angle(right, pitch.to_radians()),
+                    Quaternion::from_axis_angle(-WORLD_UP, yaw.to_radians()),
+                    Quaternion::identity(),
+                ]
+            }
+            CameraMode::Free => [
+                Quaternion::from_axis_angle(right, pitch.to_radians()),
+                Quaternion::from_axis_angle(-up, yaw.to_radians()),
+                Quaternion::from_axis_angle(forward, roll.to_radians()),
+            ],
+        };
+        for q in rotations {
+            forward = forward.rotate_by(q).normalized();
+            up = up.rotate_by(q);
         }
 
-        // Rotación con flechas
+        self.up = match self.mode {
+            CameraMode::Ecliptic => WORLD_UP,
+            CameraMode::Free => orthonormal_up(forward, up),
+        };
+        self.target = self.position + forward * distance;
+    }
+
+    /// Empieza a orbitar un cuerpo desde la posición actual
+    pub fn track(&mut self, body: BodyId, body_position: DVec3) {
+        self.is_warping = false;
+        self.target_position = None;
+        self.warp_body = None;
+        self.tracking = Some(body);
+        let offset = (self.position - body_position).to_f32();
+        self.track_offset = if offset.length() > 1e-6 { offset } else { Vector3::new(0.0, 0.0, 1.0) };
+    }
+
+    /// Arrastrar con el botón izquierdo (o mover el mouse capturado) y las
+    /// flechas orbitan alrededor del cuerpo; la rueda acerca y aleja
+    fn update_orbit(&mut self, rl: &RaylibHandle, delta_time: f32) {
+        let (mut yaw, mut pitch) = (0.0, 0.0);
         if rl.is_key_down(KeyboardKey::KEY_LEFT) {
-            let angle = -self.rotation_speed * delta_time * std::f32::consts::PI / 180.0;
-            self.rotate_camera(angle);
+            yaw -= self.rotation_speed * delta_time;
         }
         if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-            let angle = self.rotation_speed * delta_time * std::f32::consts::PI / 180.0;
-            self.rotate_camera(angle);
+            yaw += self.rotation_speed * delta_time;
+        }
+        if self.mouse_captured || rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            // Se arrastra la escena: a la derecha la cámara va a la izquierda
+            let delta = rl.get_mouse_delta() * self.mouse_sensitivity;
+            yaw -= delta.x;
+            pitch += delta.y;
+        }
+        self.orbit(yaw, pitch);
+
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.track_offset *= ZOOM_STEP.powf(wheel);
+        }
+    }
+
+    /// Gira la cámara alrededor del cuerpo: yaw positivo la mueve a su
+    /// derecha y pitch positivo hacia arriba. En el modo eclíptico el giro
+    /// es alrededor de Y y la elevación se limita; en el libre se gira
+    /// sobre los ejes de la cámara, sin polos.
+    fn orbit(&mut self, yaw: f32, mut pitch: f32) {
+        if yaw == 0.0 && pitch == 0.0 {
+            return;
+        }
+        let distance = self.track_offset.length();
+        let forward = -self.track_offset / distance;
+        let mut up = orthonormal_up(forward, self.up);
+        let right = forward.cross(up).normalized();
+
+        let rotations = match self.mode {
+            CameraMode::Ecliptic => {
+                if pitch != 0.0 {
+                    let elevation = (self.track_offset.y / distance).clamp(-1.0, 1.0).asin().to_degrees();
+                    pitch = (elevation + pitch).clamp(-MAX_PITCH, MAX_PITCH) - elevation;
+                }
+                let right = forward.cross(WORLD_UP).normalized();
+                [
+                    Quaternion::from_axis_angle(WORLD_UP, yaw.to_radians()),
+                    Quaternion::from_axis_angle(right, -pitch.to_radians()),
+                ]
+            }
+            CameraMode::Free => [
+                Quaternion::from_axis_angle(up, yaw.to_radians()),
+                Quaternion::from_axis_angle(right, -pitch.to_radians()),
+            ],
+        };
+        let mut offset = self.track_offset;
+        for q in rotations {
+            offset = offset.rotate_by(q);
+            up = up.rotate_by(q);
         }
+        self.track_offset = offset.normalized() * distance;
+
+        self.up = match self.mode {
+            CameraMode::Ecliptic => WORLD_UP,
+            CameraMode::Free => orthonormal_up(-self.track_offset.normalized(), up),
+        };
     }
 
-    fn rotate_camera(&mut self, angle: f32) {
-        let direction = self.camera.target - self.camera.position;
-        let rotated = Vector3::new(
-            direction.x * angle.cos() - direction.z * angle.sin(),
-            direction.y,
-            direction.x * angle.sin() + direction.z * angle.cos(),
-        );
-        self.camera.target = self.camera.position + rotated;
+    /// Lleva la cámara junto al cuerpo seguido, ya movido en este frame. El
+    /// zoom no baja del radio más `margin`; un cuerpo quitado deja de seguirse.
+    pub fn follow(&mut self, bodies: &BodyRegistry, margin: f32) {
+        let Some(id) = self.tracking.filter(|_| !self.is_warping) else {
+            return;
+        };
+        let body = &bodies[id];
+        if !body.active {
+            self.tracking = None;
+            return;
+        }
+        let min_distance = (body.radius + margin) * 1.1;
+        let distance = self.track_offset.length();
+        if distance < min_distance {
+            self.track_offset *= min_distance / distance;
+        }
+        self.position = body.position + self.track_offset;
+        self.target = body.position;
     }
 
-    pub fn start_warp(&mut self, target: Vector3) {
+    /// Warp por `path` mirando al llegar a su final menos `offset`; con la
+    /// escala real los cuerpos son tan chicos que el desvío tiene que
+    /// achicarse con ellos
+    pub fn start_warp(&mut self, path: WarpPath, offset: Vector3) {
+        let target = path.end();
+        self.tracking = None;
+        self.warp_body = None;
         self.is_warping = true;
         self.warp_progress = 0.0;
-        self.start_warp_pos = self.camera.position;
+        self.warp_path = path;
+        self.warp_fovy = (self.fovy, self.fovy);
         self.target_position = Some(target);
-        self.warp_target_body_pos = target - Vector3::new(0.0, 5.0, 15.0);
+        self.warp_offset = offset;
+        self.warp_target_body_pos = target - self.warp_offset;
+    }
+
+    /// Warp a un cuerpo que se mueve: `path` termina a `offset` del cuerpo
+    /// y el destino se corrige cada frame con `retarget_warp`
+    pub fn warp_to_body(&mut self, body: BodyId, path: WarpPath, offset: Vector3) {
+        self.start_warp(path, offset);
+        self.warp_body = Some(body);
+    }
+
+    /// Campo de visión al que llega el warp en curso
+    pub fn warp_fovy_to(&mut self, fovy: f32) {
+        self.warp_fovy.1 = fovy;
+    }
+
+    /// Cuerpo del warp en curso, si va a uno
+    pub fn warp_body(&self) -> Option<BodyId> {
+        self.warp_body.filter(|_| self.is_warping)
+    }
+
+    /// Segundos de pantalla que le quedan al warp
+    pub fn warp_time_left(&self) -> f32 {
+        if self.is_warping { (1.0 - self.warp_progress) / WARP_RATE } else { 0.0 }
+    }
+
+    /// Nuevo destino del warp: dónde estará el cuerpo al llegar
+    pub fn retarget_warp(&mut self, body_position: DVec3) {
+        let target = body_position + self.warp_offset;
+        self.target_position = Some(target);
+        self.warp_path.set_end(target);
+        self.warp_target_body_pos = body_position;
     }
 
     fn update_warp(&mut self, delta_time: f32) {
-        self.warp_progress += delta_time * 2.0;
+        self.warp_progress += delta_time * WARP_RATE;
 
         if self.warp_progress >= 1.0 {
             self.warp_progress = 1.0;
             self.is_warping = false;
+            self.fovy = self.warp_fovy.1;
             if let Some(target) = self.target_position {
-                self.camera.position = target;
-                self.camera.target = self.warp_target_body_pos;
+                self.position = target;
+                self.target = self.warp_target_body_pos;
+            }
+            // Desde aquí `follow` acompaña al cuerpo
+            if let Some(body) = self.warp_body.take().filter(|_| self.co_move) {
+                self.tracking = Some(body);
+                self.track_offset = self.warp_offset;
             }
-        } else if let Some(target) = self.target_position {
-            // Ease-in-out
-            let t = self.warp_progress;
+        } else if self.target_position.is_some() {
+            // Ease-in-out a lo largo del camino
+            let t = self.warp_progress as f64;
             let smooth_t = t * t * (3.0 - 2.0 * t);
-            
-            self.camera.position = Vector3::new(
-                self.start_warp_pos.x + (target.x - self.start_warp_pos.x) * smooth_t,
-                self.start_warp_pos.y + (target.y - self.start_warp_pos.y) * smooth_t,
-                self.start_warp_pos.z + (target.z - self.start_warp_pos.z) * smooth_t,
-            );
-            let start_target = self.camera.target;
-            self.camera.target = Vector3::new(
-                start_target.x + (self.warp_target_body_pos.x - start_target.x) * smooth_t * 0.5,
-                start_target.y + (self.warp_target_body_pos.y - start_target.y) * smooth_t * 0.5,
-                start_target.z + (self.warp_target_body_pos.z - start_target.z) * smooth_t * 0.5,
-            );
-        }
-    }
-
-    pub fn apply_collision(&mut self, collision_point: Vector3, safe_distance: f32) {
-        let direction = (self.camera.position - collision_point).normalized();
+
+            self.position = self.warp_path.point_at(smooth_t);
+            let (start_fovy, end_fovy) = self.warp_fovy;
+            self.fovy = start_fovy + (end_fovy - start_fovy) * smooth_t as f32;
+            let start_target = self.target;
+            self.target = start_target + (self.warp_target_body_pos - start_target) * (smooth_t * 0.5);
+        }
+    }
+
+    /// Coloca la cámara de inmediato, cancelando un warp en curso
+    pub fn look_from(&mut self, position: DVec3, target: DVec3) {
+        self.tracking = None;
+        self.warp_body = None;
+        self.is_warping = false;
+        self.target_position = None;
+        self.position = position;
+        self.target = target;
+    }
+
+    pub fn apply_collision(&mut self, collision_point: DVec3, safe_distance: f32) {
+        let direction = (self.position - collision_point).to_f32().normalized();
         let new_pos = collision_point + direction * safe_distance;
-        
-        let view_dir = self.camera.target - self.camera.position;
-        self.camera.position = new_pos;
-        self.camera.target = new_pos + view_dir;
+
+        let view_dir = self.target - self.position;
+        self.position = new_pos;
+        self.target = new_pos + view_dir;
     }
-}
\ No newline at end of file
+}
+
+/// `up` sin la componente sobre `forward`; si quedan paralelos (mirando
+/// justo arriba o abajo) se usa el eje Z para no degenerar la vista
+fn orthonormal_up(forward: Vector3, up: Vector3) -> Vector3 {
+    let up = up - forward * forward.dot(up);
+    if up.length() > 1e-6 {
+        up.normalized()
+    } else {
+        let fallback = Vector3::new(0.0, 0.0, -1.0);
+        (fallback - forward * forward.dot(fallback)).normalized()
+    }
+}
@@ -1,30 +1,63 @@
 use raylib::prelude::*;
 use crate::shader::{BodyType, ShaderManager};
+use crate::orbital_elements::OrbitalElements;
+use crate::body_registry::BodyId;
+use crate::rings::RingSystem;
+use crate::floating_origin::DVec3;
+use crate::physical::PhysicalData;
 
 pub struct CelestialBody {
     pub name: String,
-    pub position: Vector3,
+    /// Posición del mundo; al dibujar se pasa relativa a la cámara
+    pub position: DVec3,
     pub radius: f32,
     pub color: Color,
-    pub orbital_radius: f32,
+    pub orbit: OrbitalElements,
     pub orbital_speed: f32,
     pub rotation_speed: f32,
     pub rotation_angle: f32,
-    pub orbital_angle: f32,
+    /// Anomalía media en grados. En doble precisión para que los cuerpos
+    /// lentos y lejanos avancen suave en la escala real
+    pub orbital_angle: f64,
     pub is_sun: bool,
-    pub parent: Option<usize>,
+    pub parent: Option<BodyId>,
     pub texture: Option<Texture2D>,
     pub body_type: BodyType,
+    pub mass: f32,
+    pub velocity: Vector3,
+    pub active: bool,
+    /// Nombre del shader procedural (por defecto el del cuerpo)
+    pub shader: String,
+    pub ring: Option<RingSystem>,
+    /// Color base de la línea de órbita
+    pub orbit_color: Color,
+    /// Registrar la trayectoria recorrida (ver `TrailSystem`)
+    pub trail: bool,
+    /// Oblicuidad en grados respecto al plano de su órbita; más de 90° es
+    /// rotación retrógrada (Venus)
+    pub axial_tilt: f32,
+    /// Si es verdadero los elementos de la órbita se miden sobre el ecuador
+    /// del padre en lugar del plano de referencia
+    pub equatorial_orbit: bool,
+    /// Marco en el que se miden los elementos; lo actualiza el registro
+    pub orbit_frame: Quaternion,
+    /// Radio real en km y semieje mayor real en UA, para las escalas real
+    /// y logarítmica (ver `ScaleSystem`)
+    pub true_radius: Option<f32>,
+    pub true_semi_major_axis: Option<f32>,
+    /// Día sidéreo, albedo, temperatura y atmósfera del escenario (ver
+    /// `PhysicalData`); las propiedades derivadas salen de `PhysicalProperties`
+    pub physical: PhysicalData,
 }
 
 impl CelestialBody {
     pub fn new_sun(name: &str, radius: f32, color: Color) -> Self {
         Self {
             name: name.to_string(),
-            position: Vector3::zero(),
+            position: DVec3::zero(),
             radius,
             color,
-            orbital_radius: 0.0,
+            orbit: OrbitalElements::circular(0.0),
             orbital_speed: 0.0,
             rotation_speed: 0.5,
             rotation_angle: 0.0,
@@ -33,6 +66,19 @@ impl CelestialBody {
             parent: None,
             texture: None,
             body_type: BodyType::GasGiant,
+            mass: 1.0,
+            velocity: Vector3::zero(),
+            active: true,
+            shader: name.to_string(),
+            ring: None,
+            orbit_color: Color::new(100, 100, 100, 100),
+            trail: true,
+            axial_tilt: 0.0,
+            equatorial_orbit: false,
+            orbit_frame: Quaternion::identity(),
+            true_radius: None,
+            true_semi_major_axis: None,
+            physical: PhysicalData::default(),
         }
     }
 
@@ -40,7 +86,7 @@ impl CelestialBody {
         name: &str,
         radius: f32,
         color: Color,
-        orbital_radius: f32,
+        orbit: OrbitalElements,
         orbital_speed: f32,
         rotation_speed: f32,
     ) -> Self {
@@ -48,71 +94,92 @@ impl CelestialBody {
         
         Self {
             name: name.to_string(),
-            position: Vector3::new(orbital_radius, 0.0, 0.0),
+            position: orbit.precise_position_at(orbit.mean_anomaly_epoch as f64),
             radius,
             color,
-            orbital_radius,
+            orbit,
             orbital_speed,
             rotation_speed,
             rotation_angle: 0.0,
-            orbital_angle: 0.0,
+            orbital_angle: orbit.mean_anomaly_epoch as f64,
             is_sun: false,
             parent: None,
             texture: None,
             body_type,
+            mass: 0.0,
+            velocity: Vector3::zero(),
+            active: true,
+            shader: name.to_string(),
+            ring: None,
+            orbit_color: Color::new(100, 100, 100, 100),
+            trail: true,
+            axial_tilt: 0.0,
+            equatorial_orbit: false,
+            orbit_frame: Quaternion::identity(),
+            true_radius: None,
+            true_semi_major_axis: None,
+            physical: PhysicalData::default(),
         }
     }
 
-    pub fn new_moon(
-        name: &str,
-        radius: f32,
-        color: Color,
-        orbital_radius: f32,
-        orbital_speed: f32,
-        rotation_speed: f32,
-        parent_index: usize,
-    ) -> Self {
-        let mut moon = Self::new_planet(name, radius, color, orbital_radius, orbital_speed, rotation_speed);
-        moon.parent = Some(parent_index);
-        moon.body_type = BodyType::Moon;
-        moon.body_type = BodyType::Moon;
-        moon
+    /// Masa en masas solares (ver `physics::GRAVITATIONAL_CONSTANT`)
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
     }
 
-    pub fn update(&mut self, delta_time: f32, parent_position: Option<Vector3>) {
-        self.rotation_angle += self.rotation_speed * delta_time;
-        if self.rotation_angle > 360.0 {
-            self.rotation_angle -= 360.0;
-        }
+    pub fn spin(&mut self, delta_time: f32) {
+        // rem_euclid mantiene el ángulo en [0, 360) también en reversa
+        self.rotation_angle = (self.rotation_angle + self.rotation_speed * delta_time).rem_euclid(360.0);
+    }
 
+    /// Avanza la anomalía media sin mover el cuerpo. En N-cuerpos los rieles
+    /// siguen corriendo así, para que el reloj y los rieles no se separen.
+    pub fn advance_rails(&mut self, delta_time: f32) {
         if !self.is_sun {
-            self.orbital_angle += self.orbital_speed * delta_time;
-            if self.orbital_angle > 360.0 {
-                self.orbital_angle -= 360.0;
-            }
+            // orbital_angle es la anomalía media: avanza uniforme en el tiempo
+            let advance = self.orbital_speed as f64 * delta_time as f64;
+            self.orbital_angle = (self.orbital_angle + advance).rem_euclid(360.0);
+        }
+    }
 
-            let base_pos = parent_position.unwrap_or(Vector3::zero());
-            
-            self.position = Vector3::new(
-                base_pos.x + self.orbital_radius * self.orbital_angle.to_radians().cos(),
-                base_pos.y,
-                base_pos.z + self.orbital_radius * self.orbital_angle.to_radians().sin(),
-            );
+    pub fn update(&mut self, delta_time: f32, parent_position: Option<DVec3>) {
+        self.spin(delta_time);
+        self.advance_rails(delta_time);
+
+        if !self.is_sun {
+            let base_pos = parent_position.unwrap_or(DVec3::zero());
+            self.position = base_pos + self.orbit.precise_position_at(self.orbital_angle).rotate_by(self.orbit_frame);
         }
     }
 
+    /// Orientación del ecuador sin el giro diario: el plano de la órbita
+    /// inclinado por la oblicuidad. Las órbitas giran alrededor de -Y, así
+    /// que el polo de giro del modelo es -Y local.
+    pub fn equator_rotation(&self) -> Quaternion {
+        let tilt = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), self.axial_tilt.to_radians());
+        self.orbit_frame * self.orbit.plane_rotation() * tilt
+    }
+
+    /// Orientación completa para dibujar: ecuador más el giro diario
+    pub fn orientation(&self) -> Quaternion {
+        let spin = Quaternion::from_axis_angle(Vector3::new(0.0, -1.0, 0.0), self.rotation_angle.to_radians());
+        self.equator_rotation() * spin
+    }
+
     pub fn draw<D: RaylibDraw3D>(
         &self,
         d: &mut D,
         _model: &Model,
         _shader_manager: Option<&ShaderManager>,
         _time: f32,
+        origin: DVec3,
     ) {
-        d.draw_sphere(self.position, self.radius, self.color);
+        d.draw_sphere(self.position.relative_to(origin), self.radius, self.color);
     }
 
-    pub fn check_collision(&self, point: Vector3, safe_distance: f32) -> bool {
+    pub fn check_collision(&self, point: DVec3, safe_distance: f32) -> bool {
         let distance = (self.position - point).length();
-        distance < (self.radius + safe_distance)
+        distance < (self.radius + safe_distance) as f64
     }
 }
\ No newline at end of file
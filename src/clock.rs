@@ -0,0 +1,112 @@
+/// Multiplicadores de tiempo disponibles (1x hasta 1,000,000x)
+const TIME_SCALES: [f64; 7] = [1.0, 10.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+
+/// Reloj de simulación separado del tiempo real. La cámara y los efectos
+/// siguen usando el delta de pantalla; los cuerpos, la nave y los shaders
+/// reciben el delta de este reloj.
+pub struct SimulationClock {
+    time: f64,
+    scale_index: usize,
+    pub paused: bool,
+    pub reversed: bool,
+    step_requested: bool,
+    /// Tope del multiplicador mientras la integración no aguanta más (ver
+    /// `physics::MAX_TIME_SCALE`); el elegido se recupera al quitarlo
+    pub max_scale: Option<f64>,
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            scale_index: 0,
+            paused: false,
+            reversed: false,
+            step_requested: false,
+            max_scale: None,
+        }
+    }
+
+    /// Avanza el reloj y devuelve el delta de simulación (negativo en reversa)
+    pub fn tick(&mut self, wall_delta: f32) -> f32 {
+        let running = !self.paused || self.step_requested;
+        self.step_requested = false;
+        if !running {
+            return 0.0;
+        }
+
+        let direction = if self.reversed { -1.0 } else { 1.0 };
+        let sim_delta = wall_delta as f64 * self.scale() * direction;
+        self.time += sim_delta;
+        sim_delta as f32
+    }
+
+    /// Tiempo de simulación transcurrido en segundos
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Salta a un instante y deja el reloj en pausa para mirar lo que pasa ahí
+    pub fn jump_to(&mut self, time: f64) {
+        self.time = time;
+        self.paused = true;
+    }
+
+    pub fn scale(&self) -> f64 {
+        let scale = TIME_SCALES[self.scale_index];
+        self.max_scale.map_or(scale, |max| scale.min(max))
+    }
+
+    /// Verdadero si el tope está frenando el multiplicador elegido
+    pub fn is_capped(&self) -> bool {
+        self.max_scale.is_some_and(|max| TIME_SCALES[self.scale_index] > max)
+    }
+
+    /// Segundos de simulación por segundo real en este momento (0 en pausa,
+    /// negativo en reversa)
+    pub fn rate(&self) -> f64 {
+        match (self.paused, self.reversed) {
+            (true, _) => 0.0,
+            (false, true) => -self.scale(),
+            (false, false) => self.scale(),
+        }
+    }
+
+    /// No pasa del tope: con él activo el multiplicador no sube más
+    pub fn faster(&mut self) {
+        let next = (self.scale_index + 1).min(TIME_SCALES.len() - 1);
+        if self.max_scale.is_none_or(|max| TIME_SCALES[next] <= max) {
+            self.scale_index = next;
+        }
+    }
+
+    pub fn slower(&mut self) {
+        self.scale_index = self.scale_index.saturating_sub(1);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+
+    /// Avanza un solo frame; si el reloj corría, queda en pausa
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step_requested = true;
+    }
+
+    pub fn status_label(&self) -> String {
+        let state = if self.paused {
+            "||"
+        } else if self.reversed {
+            "<<"
+        } else {
+            ">>"
+        };
+        let cap = if self.is_capped() { " (tope)" } else { "" };
+        format!("{} x{}{}", state, self.scale() as u64, cap)
+    }
+}
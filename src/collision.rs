@@ -1,5 +1,5 @@
-use raylib::prelude::*;
-use crate::celestial_body::CelestialBody;
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
 
 pub struct CollisionSystem {
     pub safe_distance: f32,
@@ -12,12 +12,12 @@ impl CollisionSystem {
 
     pub fn check_and_resolve(
         &self,
-        position: Vector3,
-        bodies: &[CelestialBody],
-    ) -> Option<Vector3> {
-        for body in bodies {
-            if body.check_collision(position, self.safe_distance) {
-                let direction = (position - body.position).normalized();
+        position: DVec3,
+        bodies: &BodyRegistry,
+    ) -> Option<DVec3> {
+        for body in bodies.iter() {
+            if body.active && body.check_collision(position, self.safe_distance) {
+                let direction = (position - body.position).to_f32().normalized();
                 let safe_pos = body.position + direction * (body.radius + self.safe_distance);
                 return Some(safe_pos);
             }
@@ -25,12 +25,41 @@ impl CollisionSystem {
         None
     }
 
-    pub fn is_colliding(&self, position: Vector3, bodies: &[CelestialBody]) -> bool {
-        for body in bodies {
-            if body.check_collision(position, self.safe_distance) {
+    pub fn is_colliding(&self, position: DVec3, bodies: &BodyRegistry) -> bool {
+        for body in bodies.iter() {
+            if body.active && body.check_collision(position, self.safe_distance) {
                 return true;
             }
         }
         false
     }
+
+    /// Primer cuerpo (el más cercano a `a`) cuya esfera de seguridad corta
+    /// el segmento `a`-`b`, junto con el punto del segmento más cercano a su
+    /// centro. Los cuerpos de `ignore` no cuentan.
+    pub fn segment_hit(
+        &self,
+        a: DVec3,
+        b: DVec3,
+        bodies: &BodyRegistry,
+        ignore: &[BodyId],
+    ) -> Option<(BodyId, DVec3)> {
+        let segment = b - a;
+        let length_sq = segment.dot(segment);
+        bodies
+            .ids()
+            .filter(|id| bodies[*id].active && !ignore.contains(id))
+            .filter_map(|id| {
+                let body = &bodies[id];
+                let t = if length_sq > 0.0 {
+                    ((body.position - a).dot(segment) / length_sq).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let closest = a + segment * t;
+                body.check_collision(closest, self.safe_distance).then_some((id, closest, t))
+            })
+            .min_by(|x, y| x.2.total_cmp(&y.2))
+            .map(|(id, closest, _)| (id, closest))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use rand::Rng;
+use raylib::prelude::*;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+use crate::physics::PhysicsSystem;
+use crate::shader::BodyType;
+
+/// Distancia a la estrella a la que el núcleo empieza a sublimar
+/// (~3 UA con la Tierra a 30 unidades)
+pub const ACTIVITY_RADIUS: f32 = 90.0;
+/// Partículas por segundo de cada cola con actividad máxima
+const SPAWN_RATE: f32 = 160.0;
+const ION_LIFETIME: f32 = 1.6;
+const DUST_LIFETIME: f32 = 3.0;
+/// Velocidad de la cola de iones con actividad máxima; fija su largo
+const ION_SPEED: f32 = 18.0;
+/// Empuje de la presión de radiación sobre el polvo y rezago respecto al
+/// núcleo. La combinación curva la cola hacia atrás sobre la órbita.
+const DUST_PUSH: f32 = 3.5;
+const DUST_DRAG: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TailKind {
+    Ion,
+    Dust,
+}
+
+/// Partícula de cola, relativa al núcleo para que la cola acompañe al
+/// cometa con cualquier multiplicador de tiempo
+struct TailParticle {
+    offset: Vector3,
+    velocity: Vector3,
+    acceleration: Vector3,
+    lifetime: f32,
+    max_lifetime: f32,
+    kind: TailKind,
+}
+
+struct CometTail {
+    body: BodyId,
+    particles: Vec<TailParticle>,
+    spawn_budget: f32,
+    activity: f32,
+}
+
+/// Coma y colas de todos los cometas del registro
+pub struct CometSystem {
+    comets: Vec<CometTail>,
+    /// Escala de longitudes respecto a la escala artística: estira el radio
+    /// de actividad y el largo de las colas
+    pub length_scale: f32,
+}
+
+impl CometSystem {
+    pub fn new(bodies: &BodyRegistry) -> Self {
+        let comets = bodies
+            .ids()
+            .filter(|&id| bodies[id].body_type == BodyType::Comet)
+            .map(|body| CometTail {
+                body,
+                particles: Vec::new(),
+                spawn_budget: 0.0,
+                activity: 0.0,
+            })
+            .collect();
+        Self { comets, length_scale: 1.0 }
+    }
+
+    /// Las partículas avanzan con el tiempo de pantalla, pero se congelan
+    /// cuando el reloj de simulación está detenido
+    pub fn update(&mut self, bodies: &BodyRegistry, physics: &PhysicsSystem, delta_time: f32) {
+        let mut rng = rand::rng();
+        let scale = self.length_scale;
+
+        for comet in &mut self.comets {
+            let body = &bodies[comet.body];
+            let star_id = bodies.star_of(comet.body);
+            let star = star_id.map_or(DVec3::zero(), |s| bodies[s].position);
+
+            let to_nucleus = (body.position - star).to_f32();
+            let distance = to_nucleus.length().max(1e-3);
+            let anti_sun = to_nucleus / distance;
+            // Brillo ∝ r^-2: cero en ACTIVITY_RADIUS y máximo a un tercio
+            comet.activity = if body.active {
+                (((ACTIVITY_RADIUS * scale / distance).powi(2) - 1.0) / 8.0).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            // En N-cuerpos la órbita de los rieles no es la que se recorre
+            let motion = star_id.map_or(Vector3::zero(), |s| physics.relative_velocity(bodies, comet.body, s));
+            let trailing = if motion.length() > 0.0 { -motion.normalized() } else { Vector3::zero() };
+
+            comet.spawn_budget += SPAWN_RATE * comet.activity * delta_time;
+            while comet.spawn_budget >= 1.0 {
+                comet.spawn_budget -= 1.0;
+                let jitter = Vector3::new(
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                );
+                let start = jitter * (body.radius * 0.5);
+
+                comet.particles.push(TailParticle {
+                    offset: start,
+                    velocity: (anti_sun * (ION_SPEED * comet.activity) + jitter * 0.4) * scale,
+                    acceleration: Vector3::zero(),
+                    lifetime: 0.0,
+                    max_lifetime: ION_LIFETIME * rng.random_range(0.7..1.0),
+                    kind: TailKind::Ion,
+                });
+                comet.particles.push(TailParticle {
+                    offset: start,
+                    velocity: (trailing * (DUST_DRAG * comet.activity) + jitter * 0.3) * scale,
+                    acceleration: anti_sun * (DUST_PUSH * comet.activity * scale),
+                    lifetime: 0.0,
+                    max_lifetime: DUST_LIFETIME * rng.random_range(0.6..1.0),
+                    kind: TailKind::Dust,
+                });
+            }
+
+            comet.particles.retain_mut(|particle| {
+                particle.lifetime += delta_time;
+                particle.velocity += particle.acceleration * delta_time;
+                particle.offset += particle.velocity * delta_time;
+                particle.lifetime < particle.max_lifetime
+            });
+        }
+    }
+
+    pub fn draw<D: RaylibDraw3D + RaylibBlendModeExt>(&self, d: &mut D, bodies: &BodyRegistry, origin: DVec3) {
+        let mut d = d.begin_blend_mode(BlendMode::BLEND_ADDITIVE);
+
+        for comet in &self.comets {
+            let body = &bodies[comet.body];
+            if !body.active {
+                continue;
+            }
+
+            let nucleus = body.position.relative_to(origin);
+            if comet.activity > 0.0 {
+                let coma = body.radius * (2.0 + 6.0 * comet.activity);
+                let alpha = (60.0 * comet.activity) as u8;
+                d.draw_sphere_ex(nucleus, coma, 8, 8, Color::new(200, 230, 255, alpha / 2));
+                d.draw_sphere_ex(nucleus, coma * 0.45, 8, 8, Color::new(230, 245, 255, alpha));
+            }
+
+            for particle in &comet.particles {
+                let fade = 1.0 - particle.lifetime / particle.max_lifetime;
+                let (base, size) = match particle.kind {
+                    TailKind::Ion => (Color::new(110, 170, 255, 255), 0.12),
+                    TailKind::Dust => (Color::new(255, 235, 190, 255), 0.18),
+                };
+                let alpha = (140.0 * fade * comet.activity.max(0.2)) as u8;
+                d.draw_sphere_ex(
+                    nucleus + particle.offset,
+                    size * (0.5 + fade) * self.length_scale,
+                    3,
+                    4,
+                    Color::new(base.r, base.g, base.b, alpha),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::celestial_body::CelestialBody;
+use crate::orbital_elements::OrbitalElements;
+
+/// Fecha juliana de la época J2000.0 (2000-01-01 12:00 TT)
+pub const J2000: f64 = 2451545.0;
+const DAYS_PER_CENTURY: f64 = 36525.0;
+
+/// Días de calendario por segundo de simulación. Un año terrestre equivale a
+/// una vuelta de rieles de la Tierra (360° a 3°/s = 120 s).
+pub const DAYS_PER_SIM_SECOND: f64 = 365.25 / 120.0;
+
+/// Elemento medio: valor en J2000 y tasa por siglo juliano
+type Rate = (f64, f64);
+
+/// Elementos medios J2000 (JPL, Standish: "Keplerian Elements for Approximate
+/// Positions of the Major Planets", válidos 1800-2050). La Luna usa los
+/// elementos medios geocéntricos de Meeus.
+struct MeanElements {
+    name: &'static str,
+    eccentricity: Rate,
+    inclination: Rate,
+    mean_longitude: Rate,
+    long_periapsis: Rate,
+    ascending_node: Rate,
+}
+
+const MEAN_ELEMENTS: [MeanElements; 9] = [
+    MeanElements {
+        name: "Mercurio",
+        eccentricity: (0.20563593, 0.00001906),
+        inclination: (7.00497902, -0.00594749),
+        mean_longitude: (252.25032350, 149472.67411175),
+        long_periapsis: (77.45779628, 0.16047689),
+        ascending_node: (48.33076593, -0.12534081),
+    },
+    MeanElements {
+        name: "Venus",
+        eccentricity: (0.00677672, -0.00004107),
+        inclination: (3.39467605, -0.00078890),
+        mean_longitude: (181.97909950, 58517.81538729),
+        long_periapsis: (131.60246718, 0.00268329),
+        ascending_node: (76.67984255, -0.27769418),
+    },
+    MeanElements {
+        name: "Tierra",
+        eccentricity: (0.01671123, -0.00004392),
+        inclination: (-0.00001531, -0.01294668),
+        mean_longitude: (100.46457166, 35999.37244981),
+        long_periapsis: (102.93768193, 0.32327364),
+        ascending_node: (0.0, 0.0),
+    },
+    MeanElements {
+        name: "Luna",
+        eccentricity: (0.0549, 0.0),
+        inclination: (5.1454, 0.0),
+        mean_longitude: (218.3162, 481267.8813),
+        long_periapsis: (83.3533, 4069.0137),
+        ascending_node: (125.0434, -1934.1362),
+    },
+    MeanElements {
+        name: "Marte",
+        eccentricity: (0.09339410, 0.00007882),
+        inclination: (1.84969142, -0.00813131),
+        mean_longitude: (-4.55343205, 19140.30268499),
+        long_periapsis: (-23.94362959, 0.44441088),
+        ascending_node: (49.55953891, -0.29257343),
+    },
+    MeanElements {
+        name: "Jupiter",
+        eccentricity: (0.04838624, -0.00013253),
+        inclination: (1.30439695, -0.00183714),
+        mean_longitude: (34.39644051, 3034.74612775),
+        long_periapsis: (14.72847983, 0.21252668),
+        ascending_node: (100.47390909, 0.20469106),
+    },
+    MeanElements {
+        name: "Saturno",
+        eccentricity: (0.05386179, -0.00050991),
+        inclination: (2.48599187, 0.00193609),
+        mean_longitude: (49.95424423, 1222.49362201),
+        long_periapsis: (92.59887831, -0.41897216),
+        ascending_node: (113.66242448, -0.28867794),
+    },
+    MeanElements {
+        name: "Urano",
+        eccentricity: (0.04725744, -0.00004397),
+        inclination: (0.77263783, -0.00242939),
+        mean_longitude: (313.23810451, 428.48202785),
+        long_periapsis: (170.95427630, 0.40805281),
+        ascending_node: (74.01692503, 0.04240589),
+    },
+    MeanElements {
+        name: "Neptuno",
+        eccentricity: (0.00859048, 0.00005105),
+        inclination: (1.77004347, 0.00035372),
+        mean_longitude: (-55.12002969, 218.45945325),
+        long_periapsis: (44.96476227, -0.32241464),
+        ascending_node: (131.78422574, -0.00508664),
+    },
+];
+
+fn at(rate: Rate, centuries: f64) -> f64 {
+    rate.0 + rate.1 * centuries
+}
+
+/// Modo efemérides: coloca los cuerpos en sus longitudes heliocéntricas
+/// reales para una fecha. Solo cambia ángulos y forma de la órbita; los
+/// semiejes mayores artísticos se conservan.
+pub struct Ephemeris {
+    pub enabled: bool,
+    epoch_jd: f64,
+    epoch_sim_time: f64,
+    saved: Vec<(OrbitalElements, f32, f64)>,
+}
+
+impl Ephemeris {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            epoch_jd: now_jd(),
+            epoch_sim_time: 0.0,
+            saved: Vec::new(),
+        }
+    }
+
+    /// Fecha juliana que corresponde a un instante del reloj de simulación
+    pub fn date_at(&self, sim_time: f64) -> f64 {
+        self.epoch_jd + (sim_time - self.epoch_sim_time) * DAYS_PER_SIM_SECOND
+    }
+
+    /// Activa el modo en la fecha actual del reloj o restaura los rieles artísticos
+    pub fn toggle(&mut self, bodies: &mut [CelestialBody], sim_time: f64) {
+        if self.enabled {
+            for (body, (orbit, speed, angle)) in bodies.iter_mut().zip(self.saved.drain(..)) {
+                body.orbit = orbit;
+                body.orbital_speed = speed;
+                body.orbital_angle = angle;
+            }
+            self.enabled = false;
+        } else {
+            let jd = self.date_at(sim_time);
+            self.jump_to(bodies, jd, sim_time);
+        }
+    }
+
+    /// Salta a una fecha: el instante actual del reloj pasa a ser `jd`
+    pub fn jump_to(&mut self, bodies: &mut [CelestialBody], jd: f64, sim_time: f64) {
+        if !self.enabled {
+            self.saved = bodies
+                .iter()
+                .map(|b| (b.orbit, b.orbital_speed, b.orbital_angle))
+                .collect();
+            self.enabled = true;
+        }
+        self.epoch_jd = jd;
+        self.epoch_sim_time = sim_time;
+        apply_elements(bodies, jd);
+    }
+}
+
+/// Ajusta elementos, anomalía media y movimiento medio de los cuerpos con
+/// datos en la tabla. El movimiento medio se convierte a grados por segundo
+/// de simulación para que los rieles sigan el calendario.
+fn apply_elements(bodies: &mut [CelestialBody], jd: f64) {
+    let t = (jd - J2000) / DAYS_PER_CENTURY;
+
+    for body in bodies.iter_mut() {
+        let Some(el) = MEAN_ELEMENTS.iter().find(|el| el.name == body.name) else {
+            continue;
+        };
+
+        let node = at(el.ascending_node, t);
+        let varpi = at(el.long_periapsis, t);
+        let mean_anomaly = (at(el.mean_longitude, t) - varpi).rem_euclid(360.0);
+        let daily_motion = (el.mean_longitude.1 - el.long_periapsis.1) / DAYS_PER_CENTURY;
+
+        body.orbit = OrbitalElements::new(
+            body.orbit.semi_major_axis,
+            at(el.eccentricity, t) as f32,
+            at(el.inclination, t) as f32,
+            node.rem_euclid(360.0) as f32,
+            (varpi - node).rem_euclid(360.0) as f32,
+            mean_anomaly as f32,
+        );
+        body.orbital_angle = mean_anomaly;
+        body.orbital_speed = (daily_motion * DAYS_PER_SIM_SECOND) as f32;
+    }
+}
+
+/// Primer día del calendario gregoriano (1582-10-15); antes se usa el
+/// juliano, igual que en `calendar_date`
+const GREGORIAN_START: (i32, u32, u32) = (1582, 10, 15);
+
+/// Fecha juliana de una fecha del calendario (UTC): gregoriano desde el
+/// 15 de octubre de 1582 y juliano antes
+pub fn julian_date(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> f64 {
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+    let b = if (year, month, day) >= GREGORIAN_START {
+        let a = (y as f64 / 100.0).floor();
+        2.0 - a + (a / 4.0).floor()
+    } else {
+        0.0
+    };
+    let day_fraction = (hour as f64 + minute as f64 / 60.0) / 24.0;
+
+    (365.25 * (y as f64 + 4716.0)).floor()
+        + (30.6001 * (m as f64 + 1.0)).floor()
+        + day as f64
+        + day_fraction
+        + b
+        - 1524.5
+}
+
+/// Convierte una fecha juliana a (año, mes, día, hora, minuto)
+pub fn calendar_date(jd: f64) -> (i32, u32, u32, u32, u32) {
+    let z = (jd + 0.5).floor();
+    let f = jd + 0.5 - z;
+    let a = if z < 2299161.0 {
+        z
+    } else {
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        z + 1.0 + alpha - (alpha / 4.0).floor()
+    };
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+
+    let day = (b - d - (30.6001 * e).floor()) as u32;
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 } as u32;
+    let year = if month > 2 { c - 4716.0 } else { c - 4715.0 } as i32;
+
+    let total_minutes = (f * 1440.0).round() as u32;
+    // El redondeo puede dar 24:00; se deja en 23:59 para no cambiar de día
+    let total_minutes = total_minutes.min(1439);
+    (year, month, day, total_minutes / 60, total_minutes % 60)
+}
+
+pub fn format_date(jd: f64) -> String {
+    let (year, month, day, hour, minute) = calendar_date(jd);
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, hour, minute)
+}
+
+/// Interpreta "AAAA-MM-DD" o "AAAA-MM-DD HH:MM"
+pub fn parse_date(text: &str) -> Option<f64> {
+    let mut parts = text.split_whitespace();
+    let date = parts.next()?;
+    let time = parts.next();
+    if parts.next().is_some() {
+        return None;
+    }
+
+    // Un signo inicial permite años negativos
+    let (sign, date) = match date.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, date),
+    };
+    let mut ymd = date.split('-');
+    let year: i32 = ymd.next()?.parse().ok()?;
+    let month: u32 = ymd.next()?.parse().ok()?;
+    let day: u32 = ymd.next()?.parse().ok()?;
+    if ymd.next().is_some() || !(1..=12).contains(&month) || !(1..=days_in_month(sign * year, month)).contains(&day) {
+        return None;
+    }
+    // Los diez días que se saltó la reforma gregoriana no existen
+    if (sign * year, month) == (1582, 10) && (5..15).contains(&day) {
+        return None;
+    }
+
+    let (hour, minute) = match time {
+        Some(t) => {
+            let (h, m) = t.split_once(':')?;
+            (h.parse().ok()?, m.parse().ok()?)
+        }
+        None => (0, 0),
+    };
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some(julian_date(sign * year, month, day, hour, minute))
+}
+
+/// Días del mes con la misma regla de bisiestos que `calendar_date`:
+/// cada 4 años en el calendario juliano y sin los seculares no divisibles
+/// por 400 en el gregoriano
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let leap = if (year, month) < (GREGORIAN_START.0, GREGORIAN_START.1) {
+        year.rem_euclid(4) == 0
+    } else {
+        year.rem_euclid(4) == 0 && (year.rem_euclid(100) != 0 || year.rem_euclid(400) == 0)
+    };
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Fecha juliana del reloj del sistema
+pub fn now_jd() -> f64 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    2440587.5 + seconds / 86400.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoca_j2000() {
+        assert_eq!(julian_date(2000, 1, 1, 12, 0), J2000);
+        assert_eq!(calendar_date(J2000), (2000, 1, 1, 12, 0));
+        assert_eq!(parse_date("2000-01-01 12:00"), Some(J2000));
+    }
+
+    #[test]
+    fn ida_y_vuelta_por_el_calendario() {
+        let dates = [
+            (2024, 2, 29, 0, 0),
+            (1999, 12, 31, 23, 59),
+            (1900, 3, 1, 6, 30),
+            (1582, 10, 15, 0, 0),
+            (1582, 10, 4, 0, 0),
+            (1000, 2, 29, 12, 0),
+            (2050, 7, 14, 18, 45),
+        ];
+        for date in dates {
+            let (y, mo, d, h, mi) = date;
+            assert_eq!(calendar_date(julian_date(y, mo, d, h, mi)), date);
+        }
+        // La reforma gregoriana pasa del 4 al 15 de octubre de 1582
+        assert_eq!(julian_date(1582, 10, 15, 0, 0) - julian_date(1582, 10, 4, 0, 0), 1.0);
+    }
+
+    #[test]
+    fn rechaza_fechas_imposibles() {
+        for text in ["2023-02-29", "2023-02-30", "2024-04-31", "1900-02-29", "2024-13-01", "2024-00-10", "2024-01-32", "1582-10-10", "2024-01-01 24:00", "2024-01-01 10:60"] {
+            assert_eq!(parse_date(text), None, "{}", text);
+        }
+        for text in ["2024-02-29", "2000-02-29", "1500-02-29", "2024-04-30 23:59"] {
+            assert!(parse_date(text).is_some(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+use crate::shader::BodyType;
+
+/// Segundos de simulación que cubre cada búsqueda: dos años terrestres
+pub const SEARCH_HORIZON: f64 = 240.0;
+/// Paso del barrido. El evento más corto del escenario, Fobos frente al Sol
+/// visto desde Marte, dura unos 2 s.
+const SCAN_STEP: f64 = 0.1;
+
+/// Tipo de alineación según qué queda tapado. Un eclipse de Sol visto
+/// desde un cuerpo es a la vez ese cuerpo entrando en la sombra del otro:
+/// el eclipse lunar es el eclipse de Sol visto desde la Luna.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// Un cuerpo con al menos la mitad del radio aparente de la estrella
+    /// pasa frente a ella y puede cubrir una buena parte del disco
+    Eclipse,
+    /// Un cuerpo más chico en el cielo cruza el disco de la estrella
+    Transit,
+    /// Un cuerpo tapa a otro que no es una estrella
+    Occultation,
+}
+
+/// Un cuerpo (`occulter`) pasa frente a otro (`target`) visto desde el
+/// centro de un tercero (`observer`). Los tiempos son del reloj de
+/// simulación.
+#[derive(Debug, Clone, Copy)]
+pub struct Event {
+    pub kind: EventKind,
+    pub observer: BodyId,
+    pub occulter: BodyId,
+    pub target: BodyId,
+    pub start: f64,
+    /// Instante de máxima cobertura
+    pub peak: f64,
+    pub end: f64,
+    /// En el máximo un disco queda por completo dentro del otro
+    pub total: bool,
+}
+
+impl Event {
+    pub fn label(&self) -> &'static str {
+        match (self.kind, self.total) {
+            (EventKind::Eclipse, true) => "Eclipse total",
+            (EventKind::Eclipse, false) => "Eclipse parcial",
+            (EventKind::Transit, true) => "Transito",
+            (EventKind::Transit, false) => "Transito rasante",
+            (EventKind::Occultation, true) => "Ocultacion total",
+            (EventKind::Occultation, false) => "Ocultacion parcial",
+        }
+    }
+
+    pub fn describe(&self, bodies: &BodyRegistry) -> String {
+        let (observer, occulter, target) = (
+            &bodies[self.observer].name,
+            &bodies[self.occulter].name,
+            &bodies[self.target].name,
+        );
+        format!("{}: {} frente a {} desde {}", self.label(), occulter, target, observer)
+    }
+
+    /// Cámara para ver el evento con los cuerpos en su posición del máximo:
+    /// junto a la superficie del observador mirando al objetivo, con el
+    /// cuerpo que tapa delante. Si ese cuerpo está tan cerca que no cabe la
+    /// cámara entre ambos sin chocar (Fobos desde Marte), se mira la
+    /// alineación de costado. Devuelve (posición, objetivo).
+    pub fn viewpoint(&self, bodies: &BodyRegistry, safe_distance: f32) -> (DVec3, DVec3) {
+        let (observer, occulter, target) = (&bodies[self.observer], &bodies[self.occulter], &bodies[self.target]);
+        let toward_target = (target.position - observer.position).to_f32().normalized();
+        let margin = safe_distance + 0.5;
+
+        let near = observer.radius + margin;
+        let far = (occulter.position - observer.position).length() as f32 - occulter.radius - margin;
+        if near <= far {
+            return (observer.position + toward_target * near, occulter.position);
+        }
+
+        let mut side = toward_target.cross(Vector3::new(0.0, 1.0, 0.0));
+        if side.length() < 1e-3 {
+            side = Vector3::new(1.0, 0.0, 0.0);
+        }
+        let middle = (observer.position + occulter.position) * 0.5;
+        let span = (occulter.position - observer.position).length() as f32 + observer.radius + occulter.radius;
+        let position = middle + side.normalized() * (span * 1.5 + margin) + Vector3::new(0.0, span * 0.3, 0.0);
+        (position, middle)
+    }
+}
+
+/// Dirección, distancia y radio angular de un cuerpo visto desde otro
+#[derive(Clone, Copy)]
+struct Sight {
+    direction: Vector3,
+    distance: f32,
+    angular_radius: f32,
+}
+
+/// Contacto abierto durante el barrido
+struct Contact {
+    start: f64,
+    last: f64,
+    peak: f64,
+    /// Separación / suma de radios angulares en el máximo (0 = centrados)
+    depth: f32,
+    total: bool,
+    /// Radio aparente del cuerpo que tapa / el del tapado, en el máximo
+    size_ratio: f32,
+}
+
+/// Todos los eventos con el máximo entre `from` y `to`, ordenados por el
+/// máximo. Las posiciones salen de los rieles a partir del estado actual
+/// (`now`), así que en modo N-cuerpos no son válidas. Los cometas no
+/// participan y los cuerpos desactivados tampoco.
+pub fn find_events(bodies: &BodyRegistry, now: f64, from: f64, to: f64) -> Vec<Event> {
+    let candidates: Vec<BodyId> = bodies
+        .ids()
+        .filter(|&id| bodies[id].active && bodies[id].body_type != BodyType::Comet)
+        .collect();
+    let observers: Vec<BodyId> = candidates.iter().copied().filter(|&id| !bodies[id].is_sun).collect();
+
+    let mut open: HashMap<(BodyId, BodyId, BodyId), Contact> = HashMap::new();
+    let mut events = Vec::new();
+    let steps = ((to - from) / SCAN_STEP).ceil().max(0.0) as usize;
+
+    for step in 0..=steps {
+        let time = (from + step as f64 * SCAN_STEP).min(to);
+        let positions = bodies.rails_positions(time - now);
+
+        for &observer in &observers {
+            let eye = positions[observer];
+            let sights: Vec<Option<Sight>> = candidates
+                .iter()
+                .map(|&id| {
+                    let offset = (positions[id] - eye).to_f32();
+                    let distance = offset.length();
+                    (id != observer && distance > bodies[id].radius).then(|| Sight {
+                        direction: offset / distance,
+                        distance,
+                        angular_radius: (bodies[id].radius / distance).asin(),
+                    })
+                })
+                .collect();
+
+            for (t, &target) in candidates.iter().enumerate() {
+                let Some(far) = sights[t] else { continue };
+                for (b, &occulter) in candidates.iter().enumerate() {
+                    let Some(near) = sights[b] else { continue };
+                    if b == t || bodies[occulter].is_sun || near.distance >= far.distance {
+                        continue;
+                    }
+                    if !bodies[target].is_sun && !notable_occultation(bodies, observer, occulter, target) {
+                        continue;
+                    }
+                    let separation = near.direction.cross(far.direction).length().atan2(near.direction.dot(far.direction));
+                    let reach = near.angular_radius + far.angular_radius;
+                    if separation >= reach {
+                        continue;
+                    }
+
+                    let depth = separation / reach;
+                    let total = separation <= (near.angular_radius - far.angular_radius).abs();
+                    let contact = open.entry((observer, occulter, target)).or_insert(Contact {
+                        start: time,
+                        last: time,
+                        peak: time,
+                        depth,
+                        total,
+                        size_ratio: near.angular_radius / far.angular_radius,
+                    });
+                    contact.last = time;
+                    if depth < contact.depth {
+                        contact.depth = depth;
+                        contact.peak = time;
+                        contact.total = total;
+                        contact.size_ratio = near.angular_radius / far.angular_radius;
+                    }
+                }
+            }
+        }
+
+        // Los contactos que no se renovaron en este paso terminaron
+        let finished = step == steps;
+        open.retain(|&(observer, occulter, target), contact| {
+            if contact.last == time && !finished {
+                return true;
+            }
+            // Un contacto con el máximo en un borde de la ventana es la cola
+            // de un evento que queda fuera de ella
+            if contact.peak <= from || contact.peak >= to {
+                return false;
+            }
+            let kind = classify(bodies, target, contact.size_ratio);
+            events.push(Event {
+                kind,
+                observer,
+                occulter,
+                target,
+                start: contact.start,
+                peak: contact.peak,
+                end: contact.last,
+                // En un tránsito el disco contenido es el del cuerpo que pasa
+                total: contact.total && (contact.size_ratio >= 1.0 || kind == EventKind::Transit),
+            });
+            false
+        });
+    }
+
+    events.sort_by(|a, b| a.peak.total_cmp(&b.peak));
+    events
+}
+
+/// Primer evento con el máximo después de `now`
+pub fn next_event(bodies: &BodyRegistry, now: f64) -> Option<Event> {
+    find_events(bodies, now, now, now + SEARCH_HORIZON)
+        .into_iter()
+        .find(|e| e.peak > now + SCAN_STEP)
+}
+
+/// Último evento con el máximo antes de `now`
+pub fn previous_event(bodies: &BodyRegistry, now: f64) -> Option<Event> {
+    find_events(bodies, now, now - SEARCH_HORIZON, now)
+        .into_iter()
+        .rev()
+        .find(|e| e.peak < now - SCAN_STEP)
+}
+
+/// Entre tantos cuerpos casi todo tapa a algo desde algún lado; solo se
+/// informan las ocultaciones clásicas vistas desde un planeta: una luna
+/// detrás de su planeta, la luna propia tapando otro cuerpo y un planeta
+/// tapando a otro
+fn notable_occultation(bodies: &BodyRegistry, observer: BodyId, occulter: BodyId, target: BodyId) -> bool {
+    let orbits_star = |id: BodyId| bodies[id].parent.is_none_or(|p| bodies[p].is_sun);
+    orbits_star(observer)
+        && (bodies[target].parent == Some(occulter)
+            || bodies[occulter].parent == Some(observer)
+            || (orbits_star(occulter) && orbits_star(target)))
+}
+
+fn classify(bodies: &BodyRegistry, target: BodyId, size_ratio: f32) -> EventKind {
+    if !bodies[target].is_sun {
+        EventKind::Occultation
+    } else if size_ratio >= 0.5 {
+        EventKind::Eclipse
+    } else {
+        EventKind::Transit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+    use crate::orbital_elements::OrbitalElements;
+
+    /// Sol, planeta a 30 unidades (1°/s) y una luna chica a 3 unidades
+    /// (36°/s) que arranca a 90° del planeta. Vista desde el planeta, la
+    /// luna cruza el Sol cuando le lleva 180° (t = 90/35 s); vista desde la
+    /// luna, el planeta tapa el Sol cuando le lleva 360° (t = 270/35 s).
+    fn system() -> (BodyRegistry, [BodyId; 3]) {
+        let mut bodies = BodyRegistry::new();
+        let sun = bodies.insert(CelestialBody::new_sun("Sol", 5.0, Color::WHITE));
+        let planet = bodies.insert(CelestialBody::new_planet("Planeta", 2.0, Color::WHITE, OrbitalElements::circular(30.0), 1.0, 0.0));
+        let moon_orbit = OrbitalElements::new(3.0, 0.0, 0.0, 0.0, 0.0, 90.0);
+        let moon = bodies.insert(CelestialBody::new_planet("Luna", 0.1, Color::WHITE, moon_orbit, 36.0, 0.0));
+        bodies.set_parent(moon, Some(planet)).unwrap();
+        bodies.update_rails(0.0);
+        (bodies, [sun, planet, moon])
+    }
+
+    const TRANSIT_PEAK: f64 = 90.0 / 35.0;
+    const ECLIPSE_PEAK: f64 = 270.0 / 35.0;
+
+    #[test]
+    fn encuentra_el_transito_y_el_eclipse() {
+        let (bodies, [sun, planet, moon]) = system();
+        let events = find_events(&bodies, 0.0, 0.0, 10.0);
+        assert_eq!(events.len(), 2, "{:?}", events);
+
+        let transit = events[0];
+        assert_eq!((transit.kind, transit.observer, transit.occulter, transit.target), (EventKind::Transit, planet, moon, sun));
+        assert!(transit.total);
+        let eclipse = events[1];
+        assert_eq!((eclipse.kind, eclipse.observer, eclipse.occulter, eclipse.target), (EventKind::Eclipse, moon, planet, sun));
+        assert!(eclipse.total);
+
+        for (event, expected) in [(transit, TRANSIT_PEAK), (eclipse, ECLIPSE_PEAK)] {
+            assert!(event.start <= event.peak && event.peak <= event.end);
+            assert!((event.peak - expected).abs() < 2.0 * SCAN_STEP, "máximo en {} y no en {}", event.peak, expected);
+        }
+    }
+
+    #[test]
+    fn el_maximo_debe_quedar_dentro_de_la_ventana() {
+        let (bodies, _) = system();
+        assert!(find_events(&bodies, 0.0, 0.0, 2.0).is_empty());
+        // El tránsito sigue en curso en 2.7 s, pero su máximo quedó antes
+        let events = find_events(&bodies, 0.0, TRANSIT_PEAK + 0.15, 6.0);
+        assert!(events.is_empty(), "{:?}", events);
+    }
+
+    #[test]
+    fn siguiente_y_anterior_desde_el_instante_actual() {
+        let (mut bodies, _) = system();
+        let next = next_event(&bodies, 0.0).unwrap();
+        assert_eq!(next.kind, EventKind::Transit);
+        assert!(previous_event(&bodies, 0.0).is_none_or(|e| e.peak < -SCAN_STEP));
+
+        // Pasado el tránsito, el siguiente es el eclipse
+        let now = 4.0;
+        bodies.update_rails(now as f32);
+        let next = next_event(&bodies, now).unwrap();
+        assert_eq!(next.kind, EventKind::Eclipse);
+        assert!(next.peak > now);
+        let previous = previous_event(&bodies, now).unwrap();
+        assert_eq!(previous.kind, EventKind::Transit);
+        assert!((previous.peak - TRANSIT_PEAK).abs() < 2.0 * SCAN_STEP);
+    }
+
+    #[test]
+    fn clasifica_y_filtra_las_ocultaciones() {
+        let (bodies, [sun, planet, moon]) = system();
+        assert_eq!(classify(&bodies, planet, 3.0), EventKind::Occultation);
+        assert_eq!(classify(&bodies, sun, 0.6), EventKind::Eclipse);
+        assert_eq!(classify(&bodies, sun, 0.2), EventKind::Transit);
+
+        // La luna propia tapando algo, vista desde su planeta
+        assert!(notable_occultation(&bodies, planet, moon, sun));
+        // Desde una luna no se informan ocultaciones
+        assert!(!notable_occultation(&bodies, moon, planet, sun));
+    }
+}
@@ -0,0 +1,121 @@
+use raylib::prelude::*;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Posición del mundo en doble precisión. Con la escala real Neptuno queda
+/// a 30 000 unidades y un `f32` allí solo resuelve unas milésimas: los
+/// cuerpos y la cámara temblarían. La simulación guarda las posiciones con
+/// este tipo y recién al dibujar se pasan a `f32`, relativas a la cámara
+/// (origen flotante), donde los números vuelven a ser chicos.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl DVec3 {
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dot(self, other: DVec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Pierde precisión lejos del origen: solo para diferencias cortas o
+    /// para sistemas que no necesitan más (física N-cuerpos, predicciones)
+    pub fn to_f32(self) -> Vector3 {
+        Vector3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+
+    /// Posición para raylib: se resta en doble precisión y el resultado,
+    /// cercano a la cámara, cabe sin pérdida en un `f32`
+    pub fn relative_to(self, origin: DVec3) -> Vector3 {
+        (self - origin).to_f32()
+    }
+
+    /// Rota con un cuaternión de raylib, operando en doble precisión
+    pub fn rotate_by(self, q: Quaternion) -> Self {
+        let (qx, qy, qz, qw) = (q.x as f64, q.y as f64, q.z as f64, q.w as f64);
+        // v' = v + 2w(q×v) + 2q×(q×v)
+        let (tx, ty, tz) = (
+            2.0 * (qy * self.z - qz * self.y),
+            2.0 * (qz * self.x - qx * self.z),
+            2.0 * (qx * self.y - qy * self.x),
+        );
+        Self::new(
+            self.x + qw * tx + (qy * tz - qz * ty),
+            self.y + qw * ty + (qz * tx - qx * tz),
+            self.z + qw * tz + (qx * ty - qy * tx),
+        )
+    }
+}
+
+impl From<Vector3> for DVec3 {
+    fn from(v: Vector3) -> Self {
+        Self::new(v.x as f64, v.y as f64, v.z as f64)
+    }
+}
+
+impl Add for DVec3 {
+    type Output = DVec3;
+    fn add(self, other: DVec3) -> DVec3 {
+        DVec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+/// Desplazamiento corto en `f32` (desvíos de cámara, radios, offsets)
+impl Add<Vector3> for DVec3 {
+    type Output = DVec3;
+    fn add(self, other: Vector3) -> DVec3 {
+        self + DVec3::from(other)
+    }
+}
+
+impl Sub for DVec3 {
+    type Output = DVec3;
+    fn sub(self, other: DVec3) -> DVec3 {
+        DVec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Sub<Vector3> for DVec3 {
+    type Output = DVec3;
+    fn sub(self, other: Vector3) -> DVec3 {
+        self - DVec3::from(other)
+    }
+}
+
+impl AddAssign<Vector3> for DVec3 {
+    fn add_assign(&mut self, other: Vector3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign<Vector3> for DVec3 {
+    fn sub_assign(&mut self, other: Vector3) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f64> for DVec3 {
+    type Output = DVec3;
+    fn mul(self, k: f64) -> DVec3 {
+        DVec3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl Neg for DVec3 {
+    type Output = DVec3;
+    fn neg(self) -> DVec3 {
+        DVec3::new(-self.x, -self.y, -self.z)
+    }
+}
@@ -0,0 +1,165 @@
+use raylib::prelude::*;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+use crate::shader::BodyType;
+
+const HILL_COLOR: Color = Color::new(90, 220, 255, 70);
+const SOI_COLOR: Color = Color::new(255, 110, 220, 70);
+
+/// Radios de influencia gravitacional de un cuerpo respecto a su padre
+#[derive(Debug, Clone, Copy)]
+pub struct InfluenceRadii {
+    /// Esfera de Hill: r_H = a(1 - e)·∛(m / 3M). Las órbitas estables de
+    /// satélites quedan por debajo de ~r_H / 2.
+    pub hill: f32,
+    /// Esfera de influencia de Laplace: r_SOI = a·(m / M)^(2/5)
+    pub soi: f32,
+}
+
+/// Cuerpo central alrededor del cual orbita: el padre o la estrella principal
+fn central_body(bodies: &BodyRegistry, id: BodyId) -> Option<BodyId> {
+    bodies[id].parent.or(bodies.primary()).filter(|&c| c != id)
+}
+
+/// Radios de un cuerpo con masa que orbita a otro con masa
+pub fn influence_radii(bodies: &BodyRegistry, id: BodyId) -> Option<InfluenceRadii> {
+    let body = &bodies[id];
+    let central = central_body(bodies, id)?;
+    let central_mass = bodies[central].mass;
+    if body.is_sun || body.mass <= 0.0 || central_mass <= 0.0 {
+        return None;
+    }
+    let ratio = body.mass / central_mass;
+    Some(InfluenceRadii {
+        hill: body.orbit.periapsis() * (ratio / 3.0).cbrt(),
+        soi: body.orbit.semi_major_axis * ratio.powf(0.4),
+    })
+}
+
+/// Revisa que cada luna quede dentro de la esfera de influencia de su
+/// planeta en el apoapsis y avisa si supera la mitad de la esfera de Hill,
+/// donde las órbitas dejan de ser estables
+pub fn validate_moons(bodies: &BodyRegistry) -> Vec<String> {
+    let mut problems = Vec::new();
+    for id in bodies.ids() {
+        let moon = &bodies[id];
+        let Some(parent) = moon.parent.filter(|&p| !bodies[p].is_sun) else { continue };
+        let Some(radii) = influence_radii(bodies, parent) else { continue };
+        let apoapsis = moon.orbit.apoapsis();
+
+        if apoapsis > radii.soi {
+            problems.push(format!(
+                "{} llega a {:.2} de {}, fuera de su esfera de influencia ({:.2})",
+                moon.name, apoapsis, bodies[parent].name, radii.soi
+            ));
+        } else if apoapsis > radii.hill * 0.5 {
+            problems.push(format!(
+                "{} llega a {:.2} de {}, más de la mitad de su esfera de Hill ({:.2}): órbita inestable",
+                moon.name, apoapsis, bodies[parent].name, radii.hill
+            ));
+        }
+    }
+    problems
+}
+
+/// Esferas de Hill (cian) y de influencia (magenta) alrededor de cada
+/// planeta, en alambre translúcido, junto con el reporte de lunas
+pub struct InfluenceOverlay {
+    pub enabled: bool,
+    /// Resultado de `validate_moons` al cargar el escenario
+    pub report: Vec<String>,
+}
+
+impl InfluenceOverlay {
+    pub fn new(bodies: &BodyRegistry) -> Self {
+        Self {
+            enabled: false,
+            report: validate_moons(bodies),
+        }
+    }
+
+    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, bodies: &BodyRegistry, origin: DVec3) {
+        if !self.enabled {
+            return;
+        }
+        for id in bodies.ids() {
+            let body = &bodies[id];
+            let orbits_star = body.parent.is_none_or(|p| bodies[p].is_sun);
+            if !body.active || !orbits_star || body.body_type == BodyType::Comet {
+                continue;
+            }
+            let Some(radii) = influence_radii(bodies, id) else { continue };
+            let center = body.position.relative_to(origin);
+            d.draw_sphere_wires(center, radii.hill, 12, 16, HILL_COLOR);
+            d.draw_sphere_wires(center, radii.soi, 12, 16, SOI_COLOR);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+    use crate::orbital_elements::OrbitalElements;
+
+    const EARTH_MASS: f32 = 3.003e-6;
+
+    fn planet(name: &str, orbit: OrbitalElements, mass: f32) -> CelestialBody {
+        CelestialBody::new_planet(name, 0.001, Color::WHITE, orbit, 1.0, 1.0).with_mass(mass)
+    }
+
+    /// Sol, Tierra a 1 unidad y una luna a `moon_distance` de la Tierra
+    fn system(earth_eccentricity: f32, moon_distance: f32) -> (BodyRegistry, BodyId) {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(CelestialBody::new_sun("Sol", 0.005, Color::WHITE));
+        let earth_orbit = OrbitalElements::new(1.0, earth_eccentricity, 0.0, 0.0, 0.0, 0.0);
+        let earth = bodies.insert(planet("Tierra", earth_orbit, EARTH_MASS));
+        let moon = bodies.insert(planet("Luna", OrbitalElements::circular(moon_distance), 3.7e-8));
+        bodies.set_parent(moon, Some(earth)).unwrap();
+        (bodies, earth)
+    }
+
+    #[test]
+    fn radios_de_la_tierra() {
+        let (bodies, earth) = system(0.0, 0.00257);
+        let radii = influence_radii(&bodies, earth).unwrap();
+        // Valores conocidos en UA: r_H ≈ 0.0100 y r_SOI ≈ 0.00618
+        assert!((radii.hill - 0.0100).abs() < 1e-4, "{}", radii.hill);
+        assert!((radii.soi - 0.00618).abs() < 1e-4, "{}", radii.soi);
+    }
+
+    #[test]
+    fn hill_usa_el_periapsis() {
+        let (circular, earth) = system(0.0, 0.00257);
+        let (eccentric, _) = system(0.5, 0.00257);
+        let hill = influence_radii(&circular, earth).unwrap().hill;
+        let hill_eccentric = influence_radii(&eccentric, earth).unwrap().hill;
+        assert!((hill_eccentric - hill * 0.5).abs() < 1e-6);
+        // La esfera de Laplace depende del semieje, no de la excentricidad
+        assert_eq!(influence_radii(&circular, earth).unwrap().soi, influence_radii(&eccentric, earth).unwrap().soi);
+    }
+
+    #[test]
+    fn sin_masa_no_hay_radios() {
+        let (mut bodies, earth) = system(0.0, 0.00257);
+        bodies[earth].mass = 0.0;
+        assert!(influence_radii(&bodies, earth).is_none());
+        let sun = bodies.primary().unwrap();
+        assert!(influence_radii(&bodies, sun).is_none());
+    }
+
+    #[test]
+    fn reporte_de_lunas() {
+        // La Luna real (0.00257 UA) está bien dentro de las dos esferas
+        assert!(validate_moons(&system(0.0, 0.00257).0).is_empty());
+        // Entre r_H / 2 (0.005) y r_SOI (0.00618): inestable
+        let unstable = validate_moons(&system(0.0, 0.0055).0);
+        assert_eq!(unstable.len(), 1);
+        assert!(unstable[0].contains("inestable"));
+        // Fuera de la esfera de influencia
+        let outside = validate_moons(&system(0.0, 0.01).0);
+        assert_eq!(outside.len(), 1);
+        assert!(outside[0].contains("fuera"));
+    }
+}
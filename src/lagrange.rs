@@ -0,0 +1,260 @@
+use raylib::prelude::*;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+use crate::physics::PhysicsSystem;
+use crate::shader::BodyType;
+
+/// L1-L3 son inestables (naranja); L4 y L5, estables (verde)
+const COLLINEAR_COLOR: Color = Color::new(255, 160, 80, 220);
+const TRIANGULAR_COLOR: Color = Color::new(120, 255, 140, 220);
+
+/// Par primario/secundario del problema restringido de tres cuerpos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LagrangePair {
+    pub primary: BodyId,
+    pub secondary: BodyId,
+}
+
+impl LagrangePair {
+    pub fn label(&self, bodies: &BodyRegistry) -> String {
+        format!("{} - {}", bodies[self.primary].name, bodies[self.secondary].name)
+    }
+
+    /// Posiciones actuales de L1 a L5. Los puntos colineales resuelven la
+    /// ecuación de equilibrio del problema circular restringido por
+    /// Newton-Raphson; L4 y L5 están a 60° del secundario, adelante y atrás
+    /// en el sentido de su movimiento (el de la física activa). Sin masa en
+    /// alguno de los dos cuerpos los puntos no están definidos.
+    pub fn points(&self, bodies: &BodyRegistry, physics: &PhysicsSystem) -> Option<[DVec3; 5]> {
+        let (primary, secondary) = (&bodies[self.primary], &bodies[self.secondary]);
+        let total = primary.mass + secondary.mass;
+        if primary.mass <= 0.0 || secondary.mass <= 0.0 {
+            return None;
+        }
+        let mu = secondary.mass / total;
+
+        let offset = (secondary.position - primary.position).to_f32();
+        let distance = offset.length();
+        if distance < 1e-4 {
+            return None;
+        }
+        let radial = offset / distance;
+        let barycenter = primary.position + offset * mu;
+        let collinear = |guess: f32| barycenter + radial * (distance * collinear_point(mu, guess));
+
+        let hill = (mu / 3.0).cbrt();
+        let l1 = collinear(1.0 - mu - hill);
+        let l2 = collinear(1.0 - mu + hill);
+        let l3 = collinear(-1.0 - 5.0 * mu / 12.0);
+
+        // Dirección de avance: la velocidad orbital sin su parte radial
+        let velocity = physics.relative_velocity(bodies, self.secondary, self.primary);
+        let along = velocity - radial * velocity.dot(radial);
+        let ahead = if along.length() > 1e-6 { along.normalized() } else { Vector3::zero() };
+        let (sin_60, cos_60) = 60f32.to_radians().sin_cos();
+        let l4 = primary.position + (radial * cos_60 + ahead * sin_60) * distance;
+        let l5 = primary.position + (radial * cos_60 - ahead * sin_60) * distance;
+
+        Some([l1, l2, l3, l4, l5])
+    }
+}
+
+/// Raíz de la ecuación de los puntos colineales en unidades normalizadas
+/// (separación 1, baricentro en 0, primario en -μ, secundario en 1 - μ)
+fn collinear_point(mu: f32, guess: f32) -> f32 {
+    let mut x = guess;
+    for _ in 0..20 {
+        let d1 = x + mu;
+        let d2 = x - 1.0 + mu;
+        let f = x - (1.0 - mu) * d1 / d1.abs().powi(3) - mu * d2 / d2.abs().powi(3);
+        let df = 1.0 + 2.0 * (1.0 - mu) / d1.abs().powi(3) + 2.0 * mu / d2.abs().powi(3);
+        let step = f / df;
+        x -= step;
+        if step.abs() < 1e-7 {
+            break;
+        }
+    }
+    x
+}
+
+/// Puntos de Lagrange de todos los pares padre/hijo. Se dibujan todos y
+/// solo el par seleccionado lleva etiquetas y sirve de destino de warp.
+pub struct LagrangeSystem {
+    pub enabled: bool,
+    selected: usize,
+}
+
+impl LagrangeSystem {
+    pub fn new() -> Self {
+        Self { enabled: false, selected: 0 }
+    }
+
+    /// Pares con un cuerpo activo y su padre (la estrella principal si no
+    /// tiene). Los cometas no entran: su masa no alcanza para nada útil.
+    pub fn pairs(&self, bodies: &BodyRegistry) -> Vec<LagrangePair> {
+        bodies
+            .update_order()
+            .iter()
+            .filter(|&&id| {
+                let body = &bodies[id];
+                body.active && !body.is_sun && body.body_type != BodyType::Comet
+            })
+            .filter_map(|&secondary| {
+                let primary = bodies[secondary].parent.or(bodies.primary())?;
+                bodies[primary].active.then_some(LagrangePair { primary, secondary })
+            })
+            .collect()
+    }
+
+    /// Par seleccionado; el índice se ajusta si cambió la cantidad de pares
+    pub fn selected(&self, bodies: &BodyRegistry) -> Option<LagrangePair> {
+        let pairs = self.pairs(bodies);
+        pairs.get(self.selected % pairs.len().max(1)).copied()
+    }
+
+    pub fn select_next(&mut self, bodies: &BodyRegistry) {
+        let count = self.pairs(bodies).len().max(1);
+        self.selected = (self.selected + 1) % count;
+    }
+
+    /// Selecciona el par de un cuerpo como secundario
+    pub fn select_body(&mut self, bodies: &BodyRegistry, secondary: BodyId) {
+        if let Some(i) = self.pairs(bodies).iter().position(|p| p.secondary == secondary) {
+            self.selected = i;
+        }
+    }
+
+    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, bodies: &BodyRegistry, physics: &PhysicsSystem, origin: DVec3) {
+        if !self.enabled {
+            return;
+        }
+        let selected = self.selected(bodies);
+
+        for pair in self.pairs(bodies) {
+            let Some(points) = pair.points(bodies, physics) else { continue };
+            let separation = (bodies[pair.secondary].position - bodies[pair.primary].position).length() as f32;
+            let size = (separation * 0.015).clamp(0.08, 1.2);
+            let dim = if Some(pair) == selected { 1.0 } else { 0.35 };
+
+            for (i, point) in points.iter().enumerate() {
+                let base = if i < 3 { COLLINEAR_COLOR } else { TRIANGULAR_COLOR };
+                let color = Color::new(base.r, base.g, base.b, (base.a as f32 * dim) as u8);
+                d.draw_sphere_wires(point.relative_to(origin), size, 4, 6, color);
+            }
+        }
+    }
+
+    /// Etiquetas L1-L5 del par seleccionado, en pantalla. `camera` es la
+    /// cámara de dibujo, relativa a `origin`.
+    pub fn draw_labels(
+        &self,
+        d: &mut RaylibDrawHandle,
+        camera: &Camera3D,
+        bodies: &BodyRegistry,
+        physics: &PhysicsSystem,
+        origin: DVec3,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let Some(points) = self.selected(bodies).and_then(|pair| pair.points(bodies, physics)) else {
+            return;
+        };
+        let forward = (camera.target - camera.position).normalized();
+
+        for (i, point) in points.iter().enumerate() {
+            let point = point.relative_to(origin);
+            if (point - camera.position).dot(forward) <= 0.0 {
+                continue;
+            }
+            let screen = d.get_world_to_screen(point, *camera);
+            let color = if i < 3 { COLLINEAR_COLOR } else { TRIANGULAR_COLOR };
+            d.draw_text(&format!("L{}", i + 1), screen.x as i32 + 6, screen.y as i32 - 6, 14, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+    use crate::orbital_elements::OrbitalElements;
+    use crate::physics::PhysicsMode;
+
+    /// Estrella de masa 1 en el origen y un secundario en órbita circular
+    fn pair(secondary_mass: f32) -> (BodyRegistry, LagrangePair) {
+        let mut bodies = BodyRegistry::new();
+        let primary = bodies.insert(CelestialBody::new_sun("Sol", 5.0, Color::WHITE));
+        let planet = CelestialBody::new_planet("Planeta", 1.0, Color::WHITE, OrbitalElements::circular(30.0), 3.0, 1.0)
+            .with_mass(secondary_mass);
+        let secondary = bodies.insert(planet);
+        (bodies, LagrangePair { primary, secondary })
+    }
+
+    #[test]
+    fn puntos_colineales_en_equilibrio() {
+        for mass in [3e-6, 1e-3, 0.05] {
+            let (bodies, pair) = pair(mass);
+            let points = pair.points(&bodies, &PhysicsSystem::new()).unwrap();
+            let mu = mass / (1.0 + mass);
+            let offset = (bodies[pair.secondary].position - bodies[pair.primary].position).to_f32();
+            let distance = offset.length();
+            let barycenter = bodies[pair.primary].position + offset * mu;
+
+            for point in &points[..3] {
+                let relative = (*point - barycenter).to_f32() / distance;
+                let x = relative.dot(offset / distance);
+                // Sobre la recta que une los dos cuerpos
+                assert!((relative - offset / distance * x).length() < 1e-5);
+                let (d1, d2) = (x + mu, x - 1.0 + mu);
+                let residual = x - (1.0 - mu) * d1 / d1.abs().powi(3) - mu * d2 / d2.abs().powi(3);
+                assert!(residual.abs() < 1e-4, "masa {}: residuo {}", mass, residual);
+            }
+            // L1 entre los cuerpos, L2 más allá del secundario y L3 del otro lado
+            let along = |p: DVec3| (p - barycenter).to_f32().dot(offset / distance) / distance;
+            assert!(along(points[0]) > -mu && along(points[0]) < 1.0 - mu);
+            assert!(along(points[1]) > 1.0 - mu);
+            assert!(along(points[2]) < -mu);
+        }
+    }
+
+    #[test]
+    fn puntos_triangulares_a_60_grados() {
+        let (bodies, pair) = pair(1e-3);
+        let points = pair.points(&bodies, &PhysicsSystem::new()).unwrap();
+        let primary = bodies[pair.primary].position;
+        let to_secondary = (bodies[pair.secondary].position - primary).to_f32();
+        for point in &points[3..] {
+            let to_point = (*point - primary).to_f32();
+            let angle = to_point.normalized().dot(to_secondary.normalized()).acos().to_degrees();
+            assert!((angle - 60.0).abs() < 1e-3, "{}", angle);
+            // Triángulo equilátero con los dos cuerpos
+            assert!((to_point.length() - to_secondary.length()).abs() < 1e-3);
+            assert!(((*point - bodies[pair.secondary].position).length() as f32 - to_secondary.length()).abs() < 1e-3);
+        }
+        // L4 va adelante en el sentido del movimiento y L5 atrás
+        let velocity = bodies[pair.secondary].orbit.velocity_at(0.0, 1.0);
+        assert!((points[3] - primary).to_f32().dot(velocity) > 0.0);
+        assert!((points[4] - primary).to_f32().dot(velocity) < 0.0);
+    }
+
+    #[test]
+    fn en_n_cuerpos_l4_sigue_la_velocidad_integrada() {
+        let (mut bodies, pair) = pair(1e-3);
+        let mut physics = PhysicsSystem::new();
+        let rails = pair.points(&bodies, &physics).unwrap();
+        // Sobre los rieles el planeta avanza; bajo gravedad va al revés
+        physics.mode = PhysicsMode::NBody;
+        bodies[pair.secondary].velocity = -bodies[pair.secondary].orbit.velocity_at(0.0, 1.0);
+        let nbody = pair.points(&bodies, &physics).unwrap();
+        assert!((nbody[3] - rails[4]).length() < 1e-4);
+        assert!((nbody[4] - rails[3]).length() < 1e-4);
+    }
+
+    #[test]
+    fn sin_masa_no_hay_puntos() {
+        let (bodies, pair) = pair(0.0);
+        assert!(pair.points(&bodies, &PhysicsSystem::new()).is_none());
+    }
+}
@@ -6,6 +6,25 @@ mod orbit;
 mod collision;
 mod warp_effect;
 mod shader;
+mod orbital_elements;
+mod physics;
+mod clock;
+mod ephemeris;
+mod scenario;
+mod body_registry;
+mod belt;
+mod comet;
+mod rings;
+mod trail;
+mod lagrange;
+mod influence;
+mod events;
+mod alignment;
+mod scale;
+mod floating_origin;
+mod physical;
+mod warp_path;
+mod bookmarks;
 
 use raylib::prelude::*;
 use camera::CameraController;
@@ -15,15 +34,107 @@ use skybox::Skybox;
 use orbit::OrbitRenderer;
 use collision::CollisionSystem;
 use warp_effect::WarpEffect;
-use shader::{ShaderManager, BodyType};
+use shader::ShaderManager;
+use physics::{PhysicsSystem, PhysicsMode};
+use clock::SimulationClock;
+use ephemeris::Ephemeris;
+use scenario::Scenario;
+use body_registry::{BodyId, BodyRegistry};
+use belt::{Belt, BeltRenderer};
+use comet::CometSystem;
+use rings::RingRenderer;
+use trail::TrailSystem;
+use lagrange::LagrangeSystem;
+use influence::InfluenceOverlay;
+use events::Event;
+use alignment::{Alignment, AlignmentBrowser};
+use scale::{ScaleMode, ScaleSystem};
+use floating_origin::DVec3;
+use physical::PhysicalProperties;
+use warp_path::{WarpPath, WarpRequest};
+use bookmarks::{Bookmark, BookmarkPose, Bookmarks, BOOKMARKS_PATH};
 
 const SCREEN_WIDTH: i32 = 1280;
 const SCREEN_HEIGHT: i32 = 720;
 
+// Poses fijas de la cámara en la escala artística; las demás escalas las
+// alejan según la distancia de cada una
+const HOME_POSITION: Vector3 = Vector3::new(0.0, 30.0, 50.0);
+const OVERVIEW_POSITION: Vector3 = Vector3::new(0.0, 20.0, 40.0);
+const TOP_DOWN_POSITION: Vector3 = Vector3::new(0.0, 250.0, 0.01);
+/// Desvío de la cámara al llegar a un planeta o punto de Lagrange
+const WARP_OFFSET: Vector3 = Vector3::new(0.0, 5.0, 15.0);
+/// Distancia de llegada de un warp a un cuerpo, en radios del cuerpo (más
+/// el margen de colisión)
+const ARRIVAL_RADII: f32 = 6.0;
+const CAMERA_SPEED: f32 = 10.0;
+const SAFE_DISTANCE: f32 = 2.0;
+const SKYBOX_SIZE: f32 = 1000.0;
+
+const WARP_KEYS: [KeyboardKey; 9] = [
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
+];
+
+/// Segundos que un aviso queda en el panel de información
+const NOTICE_SECONDS: f32 = 4.0;
+
+/// Último aviso para el usuario (un pedido rechazado, una búsqueda sin
+/// resultados): se imprime en consola y se muestra un rato en el HUD
+struct Notice {
+    text: String,
+    remaining: f32,
+}
+
+impl Notice {
+    fn new() -> Self {
+        Self { text: String::new(), remaining: 0.0 }
+    }
+
+    fn warn(&mut self, text: String) {
+        println!("⚠️  {}", text);
+        self.text = text;
+        self.remaining = NOTICE_SECONDS;
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        self.remaining = (self.remaining - delta_time).max(0.0);
+    }
+
+    /// Texto para el HUD, sin acentos porque la fuente de raylib no los tiene
+    fn hud_text(&self) -> Option<String> {
+        let plain = |c| match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' => 'u',
+            'ñ' => 'n',
+            c => c,
+        };
+        (self.remaining > 0.0).then(|| self.text.chars().map(plain).collect())
+    }
+}
+
 fn main() {
+    let scenario = match scenario::path_from_args().and_then(|path| Scenario::load(&path)) {
+        Ok(scenario) => scenario,
+        Err(err) => {
+            eprintln!("Error en el escenario: {}", err);
+            std::process::exit(1);
+        }
+    };
+
     let (mut rl, thread) = raylib::init()
         .size(SCREEN_WIDTH, SCREEN_HEIGHT)
-        .title("Space Travel - Sistema Solar")
+        .title(&format!("Space Travel - {}", scenario.name))
         .build();
 
     rl.set_target_fps(60);
@@ -35,66 +146,239 @@ fn main() {
     let sky_tex = rl.load_texture(&thread, "assets/textures/skybox.png").ok();
     let sky_model = rl.load_model(&thread, "assets/models/sphere.obj").ok();
 
-    let mut camera_controller = CameraController::new(Vector3::new(0.0, 30.0, 50.0));
+    let mut camera_controller = CameraController::new(DVec3::from(HOME_POSITION));
     let mut spaceship = Spaceship::new();
-    let skybox = Skybox::new(1000.0, sky_model, sky_tex);
-    let orbit_renderer = OrbitRenderer::new();
-    let collision_system = CollisionSystem::new(2.0);
+    let mut skybox = Skybox::new(SKYBOX_SIZE, sky_model, sky_tex);
+    let mut orbit_renderer = OrbitRenderer::new();
+    let mut collision_system = CollisionSystem::new(SAFE_DISTANCE);
     let mut warp_effect = WarpEffect::new();
     let mut shader_manager = ShaderManager::new(&mut rl, &thread);
 
-    let mut celestial_bodies = create_solar_system();
+    let mut physics = PhysicsSystem::new();
+    let mut clock = SimulationClock::new();
+    let mut ephemeris = Ephemeris::new();
+    // Texto del comando "saltar a fecha" mientras se escribe
+    let mut date_prompt: Option<String> = None;
+    // Nombre del marcador mientras se escribe
+    let mut bookmark_prompt: Option<String> = None;
+    let mut bookmarks = Bookmarks::load(BOOKMARKS_PATH);
+
+    let mut celestial_bodies = scenario.build_registry();
+    let mut belts = scenario.build_belts(&celestial_bodies);
+    let mut belt_renderer = BeltRenderer::new(&mut rl, &thread);
+    for belt in &belts {
+        println!("✅ Cinturón '{}': {} cuerpos", belt.name, belt.len());
+    }
+    let mut comets = CometSystem::new(&celestial_bodies);
+    let mut ring_renderer = RingRenderer::new(&mut rl, &thread, &celestial_bodies);
+    let mut trails = TrailSystem::new(&celestial_bodies);
+    let mut lagrange = LagrangeSystem::new();
+    let mut influence = InfluenceOverlay::new(&celestial_bodies);
+    let mut scales = ScaleSystem::new(&celestial_bodies);
+    apply_view_scale(&scales, &mut camera_controller, &mut collision_system, &mut skybox, &mut comets, &mut orbit_renderer);
+    if influence.report.is_empty() {
+        println!("✅ Lunas dentro de la esfera de influencia de su planeta");
+    }
+    for problem in &influence.report {
+        println!("⚠️  {}", problem);
+    }
 
     // Variables de estado
+    let mut alignments = AlignmentBrowser::new(&celestial_bodies);
+    // Último evento al que se saltó; se muestra mientras dura
+    let mut current_event: Option<Event> = None;
+    // Warp pedido con Ctrl: se dibuja su camino hasta confirmarlo
+    let mut warp_preview: Option<WarpRequest> = None;
     let mut show_orbits = true;
     let mut show_info = true;
     let mut top_down = false;
-    let mut elapsed_time = 0.0f32;
     let mut texture_cache: Vec<Texture2D> = Vec::new();
     let mut frame_count = 0u32;
     let texture_refresh_rate = 10;
+    let mut notice = Notice::new();
 
     while !rl.window_should_close() {
         let delta_time = rl.get_frame_time();
-        elapsed_time += delta_time;
+        notice.update(delta_time);
+
+        // Mientras se escribe una fecha o un marcador el teclado no controla
+        // nada más
+        let typing = date_prompt.is_some() || bookmark_prompt.is_some();
+        let mut requested_warp = None;
+        if typing {
+            if let Some(jd) = handle_date_prompt(&mut rl, &mut date_prompt) {
+                ephemeris.jump_to(celestial_bodies.as_mut_slice(), jd, clock.time());
+                scales.apply(&mut celestial_bodies, &mut belts);
+                place_bodies_on_rails(&mut celestial_bodies, &physics);
+                trails.clear();
+            }
+            requested_warp = handle_bookmark_prompt(
+                &mut rl,
+                &mut bookmark_prompt,
+                &mut bookmarks,
+                &camera_controller,
+                &celestial_bodies,
+                scales.mode.label(),
+            );
+        } else {
+            handle_clock_input(&rl, &mut clock);
+        }
 
-        handle_input(&rl, &mut camera_controller, &celestial_bodies, &mut warp_effect, &mut spaceship, &mut top_down);
+        if !typing {
+            handle_camera_mode_input(&mut rl, &mut camera_controller);
+            handle_tracking_input(&rl, &mut camera_controller, &celestial_bodies, &mut top_down);
+            let warp_request = handle_input(&rl, &mut camera_controller, &celestial_bodies, &scales, &mut spaceship, &mut top_down);
 
-        if rl.is_key_pressed(KeyboardKey::KEY_O) {
-            show_orbits = !show_orbits;
+            if rl.is_key_pressed(KeyboardKey::KEY_O) {
+                show_orbits = !show_orbits;
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_I) {
+                show_info = !show_info;
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_L) {
+                trails.enabled = !trails.enabled;
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_K) {
+                trails.toggle_frame();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_U) {
+                influence.enabled = !influence.enabled;
+            }
+            let lagrange_warp = handle_lagrange_input(&rl, &mut lagrange, &celestial_bodies, &physics, &scales, &camera_controller);
+            let bookmark_warp = handle_bookmark_input(
+                &mut rl,
+                &mut bookmarks,
+                &mut bookmark_prompt,
+                &camera_controller,
+                &celestial_bodies,
+                scales.mode.label(),
+            );
+            // Con Alt los números son marcadores, no planetas
+            requested_warp = warp_request.filter(|_| !alt_down(&rl)).or(lagrange_warp).or(bookmark_warp);
+            if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                warp_preview = None;
+            }
+            handle_physics_input(&rl, &mut physics, &mut celestial_bodies, &scales, camera_controller.position, &mut notice);
+            handle_scale_input(&rl, &mut scales, &physics, &mut notice);
+
+            if let Some(event) = handle_event_input(&rl, &celestial_bodies, &physics, &ephemeris, clock.time(), &mut notice) {
+                jump_rails_to(event.peak, &mut clock, &mut celestial_bodies, &mut belts, &mut trails);
+                top_down = false;
+                let (position, target) = event.viewpoint(&celestial_bodies, collision_system.safe_distance);
+                camera_controller.look_from(position, target);
+                println!("✅ {} ({})", event.describe(&celestial_bodies), event_time_label(&ephemeris, event.peak));
+                current_event = Some(event);
+            }
+            let alignment = handle_alignment_input(
+                &rl,
+                &mut alignments,
+                &celestial_bodies,
+                &physics,
+                &ephemeris,
+                clock.time(),
+                &mut notice,
+            );
+            if let Some(alignment) = alignment {
+                jump_rails_to(alignment.time, &mut clock, &mut celestial_bodies, &mut belts, &mut trails);
+                top_down = false;
+                let (position, target) = alignment.viewpoint(&celestial_bodies, collision_system.safe_distance);
+                camera_controller.look_from(position, target);
+                println!("✅ {} ({})", alignment.describe(&celestial_bodies), event_time_label(&ephemeris, alignment.time));
+            }
+
+            if rl.is_key_pressed(KeyboardKey::KEY_E) {
+                ephemeris.toggle(celestial_bodies.as_mut_slice(), clock.time());
+                scales.apply(&mut celestial_bodies, &mut belts);
+                place_bodies_on_rails(&mut celestial_bodies, &physics);
+                trails.clear();
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_J) {
+                date_prompt = Some(String::new());
+                // Descartar la 'j' que quedó en la cola de caracteres
+                while rl.get_char_pressed().is_some() {}
+            }
+
+            camera_controller.update(&rl, delta_time);
         }
-        if rl.is_key_pressed(KeyboardKey::KEY_I) {
-            show_info = !show_info;
+        // Con Ctrl el warp solo se muestra; repetir la tecla lo confirma y
+        // Retroceso descarta la vista previa
+        if let Some(request) = requested_warp {
+            let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+            let previewed = warp_preview.as_ref().is_some_and(|p| p.same_target(&request));
+            if ctrl && !previewed {
+                warp_preview = Some(request);
+            } else {
+                warp_preview = None;
+                top_down = false;
+                start_planned_warp(&mut camera_controller, &mut warp_effect, &celestial_bodies, &collision_system, &request);
+            }
+        }
+        // Las estelas guardan posiciones de la escala anterior
+        if scales.update(delta_time, &mut celestial_bodies, &mut belts) {
+            trails.clear();
+            apply_view_scale(&scales, &mut camera_controller, &mut collision_system, &mut skybox, &mut comets, &mut orbit_renderer);
         }
-
-        camera_controller.update(&rl, delta_time);
         if top_down {
-            camera_controller.camera.position = Vector3::new(0.0, 250.0, 0.01);
-            camera_controller.camera.target = Vector3::zero();
+            camera_controller.position = scaled_pose(&scales, TOP_DOWN_POSITION);
+            camera_controller.target = DVec3::zero();
+        }
+        // El reloj avanza después de la entrada para que el tope de N-cuerpos
+        // ya valga en el frame en que se activa
+        clock.max_scale = (physics.mode == PhysicsMode::NBody).then_some(physics::MAX_TIME_SCALE);
+        let sim_delta = clock.tick(delta_time);
+        match physics.mode {
+            PhysicsMode::Rails => celestial_bodies.update_rails(sim_delta),
+            PhysicsMode::NBody => {
+                physics.step(celestial_bodies.as_mut_slice(), sim_delta);
+                for body in celestial_bodies.iter_mut() {
+                    body.spin(sim_delta);
+                    body.advance_rails(sim_delta);
+                }
+            }
         }
-        update_celestial_bodies(&mut celestial_bodies, delta_time);
-        spaceship.update(delta_time);
+        for belt in belts.iter_mut() {
+            belt.update(sim_delta);
+        }
+        trails.record(&celestial_bodies, sim_delta);
+        // Las colas viven en tiempo de pantalla para verse igual con
+        // cualquier multiplicador, pero se detienen con la pausa
+        comets.update(&celestial_bodies, &physics, if sim_delta != 0.0 { delta_time } else { 0.0 });
+        spaceship.update(sim_delta);
         warp_effect.update(delta_time);
 
+        // El warp a un cuerpo apunta a dónde estará cuando termine
+        if let Some(id) = camera_controller.warp_body() {
+            let lead = camera_controller.warp_time_left() as f64 * clock.rate();
+            camera_controller.retarget_warp(predict_position(&celestial_bodies, &physics, id, lead));
+        }
+        camera_controller.follow(&celestial_bodies, collision_system.safe_distance);
         if let Some(safe_pos) = collision_system.check_and_resolve(
-            camera_controller.camera.position,
+            camera_controller.position,
             &celestial_bodies,
         ) {
-            camera_controller.apply_collision(safe_pos, 2.0);
+            camera_controller.apply_collision(safe_pos, collision_system.safe_distance);
         }
 
+        current_event = current_event.filter(|e| (e.start..=e.end).contains(&clock.time()));
+
+        // raylib dibuja en f32 con la cámara en el origen; todo lo demás se
+        // pasa relativo a `origin`
+        let origin = camera_controller.position;
+        let render_camera = camera_controller.render_camera();
+        orbit_renderer.set_view(&render_camera, rl.get_screen_height());
+
         let mut d = rl.begin_drawing(&thread);
         
         d.clear_background(Color::BLACK);
 
         // Generar texturas una sola vez
         if texture_cache.is_empty() {
-            for body in &celestial_bodies {
+            for body in celestial_bodies.iter() {
                 let img = shader_manager.create_texture_for_body_named(
                     body.body_type,
                     body.color,
-                    elapsed_time,
-                    &body.name,
+                    clock.time() as f32,
+                    &body.shader,
                 );
                 let tex = d.load_texture_from_image(&thread, &img).unwrap();
                 texture_cache.push(tex);
@@ -103,26 +387,31 @@ fn main() {
         frame_count += 1;
 
         {
-            let mut d3 = d.begin_mode3D(camera_controller.camera);
+            let mut d3 = d.begin_mode3D(render_camera);
 
             skybox.draw(&mut d3, Vector3::zero());
             skybox.draw_stars(&mut d3, Vector3::zero());
 
             if show_orbits {
-                for body in &celestial_bodies {
-                    if !body.is_sun && body.parent.is_none() {
-                        orbit_renderer.draw_orbit(
-                            &mut d3,
-                            Vector3::zero(),
-                            body.orbital_radius,
-                            Color::new(100, 100, 100, 100),
-                        );
+                // Cada órbita se centra en la posición actual de su padre (el
+                // origen si no tiene), así las de las lunas siguen al planeta
+                for body in celestial_bodies.iter() {
+                    if body.is_sun || !body.active {
+                        continue;
                     }
+                    let center = body.parent.map_or(DVec3::zero(), |p| celestial_bodies[p].position);
+                    orbit_renderer.draw_orbit(&mut d3, center, origin, body);
+                    orbit_renderer.draw_markers(&mut d3, center, origin, body);
                 }
             }
+            trails.draw(&mut d3, &celestial_bodies, origin);
+            lagrange.draw(&mut d3, &celestial_bodies, &physics, origin);
+            influence.draw(&mut d3, &celestial_bodies, origin);
 
-            let mut saturn_pos: Option<Vector3> = None;
             for (idx, body) in celestial_bodies.iter().enumerate() {
+                if !body.active {
+                    continue;
+                }
                 if idx < texture_cache.len() {
                     let tex = &texture_cache[idx];
                     unsafe {
@@ -132,151 +421,82 @@ fn main() {
                             .texture = **tex;
                     }
                     
+                    let (axis, angle) = body.orientation().to_axis_angle();
                     d3.draw_model_ex(
                         &sphere_model,
-                        body.position,
-                        Vector3::new(0.0, 1.0, 0.0),
-                        0.0,
+                        body.position.relative_to(origin),
+                        axis,
+                        angle.to_degrees(),
                         Vector3::new(body.radius, body.radius, body.radius),
                         Color::WHITE,
                     );
                 }
                 
-                if body.name == "Saturno" { saturn_pos = Some(body.position); }
                 if body.is_sun {
-                    d3.draw_sphere(body.position, body.radius * 1.08, Color::new(255, 220, 100, 8));
+                    d3.draw_sphere(body.position.relative_to(origin), body.radius * 1.08, Color::new(255, 220, 100, 8));
                 }
             }
-            if let Some(pos) = saturn_pos {
-                let segments = 180;
-                let tilt_angle = 15.0_f32.to_radians();
-                
-                let ring_bands = [
-                    (5.2, 5.8, Color::new(200, 180, 140, 140)),
-                    (5.8, 6.3, Color::new(190, 170, 130, 150)),
-                    (6.3, 6.8, Color::new(180, 160, 120, 135)),
-                    // Gap de Cassini (6.8 - 7.1)
-                    (7.1, 7.5, Color::new(210, 190, 150, 145)),
-                    (7.5, 8.0, Color::new(195, 175, 140, 155)),
-                    (8.0, 8.5, Color::new(185, 165, 130, 140)),
-                    (8.5, 9.0, Color::new(170, 150, 115, 120)),
-                ];
-                
-                for (ring_inner, ring_outer, base_color) in &ring_bands {
-                    let num_circles = 15;
-                    for circle_idx in 0..num_circles {
-                        let t = circle_idx as f32 / num_circles as f32;
-                        let radius = ring_inner + (ring_outer - ring_inner) * t;
-                        
-                        let brightness = 1.0 + (t - 0.5) * 0.2;
-                        let circle_color = Color::new(
-                            (base_color.r as f32 * brightness).clamp(0.0, 255.0) as u8,
-                            (base_color.g as f32 * brightness).clamp(0.0, 255.0) as u8,
-                            (base_color.b as f32 * brightness).clamp(0.0, 255.0) as u8,
-                            base_color.a,
-                        );
-                        
-                        for i in 0..segments {
-                            let a1 = (i as f32 / segments as f32) * std::f32::consts::TAU;
-                            let a2 = ((i + 1) as f32 / segments as f32) * std::f32::consts::TAU;
-                            
-                            let noise_val = ((a1 * 23.0).sin() * 0.5 + 0.5) * 0.1;
-                            let varied_brightness = brightness + noise_val - 0.05;
-                            let varied_color = Color::new(
-                                (base_color.r as f32 * varied_brightness).clamp(0.0, 255.0) as u8,
-                                (base_color.g as f32 * varied_brightness).clamp(0.0, 255.0) as u8,
-                                (base_color.b as f32 * varied_brightness).clamp(0.0, 255.0) as u8,
-                                base_color.a,
-                            );
-                            
-                            let y_offset = radius * tilt_angle.sin();
-                            let z_scale = tilt_angle.cos();
-                            
-                            let p1 = Vector3::new(
-                                pos.x + radius * a1.cos(),
-                                pos.y + y_offset * a1.sin(),
-                                pos.z + radius * a1.sin() * z_scale
-                            );
-                            let p2 = Vector3::new(
-                                pos.x + radius * a2.cos(),
-                                pos.y + y_offset * a2.sin(),
-                                pos.z + radius * a2.sin() * z_scale
-                            );
-                            
-                            d3.draw_line_3D(p1, p2, varied_color);
-                        }
-                    }
-                }
+            let star = celestial_bodies.primary().map_or(DVec3::zero(), |id| celestial_bodies[id].position);
+            for belt in &belts {
+                belt_renderer.draw(&mut d3, belt, star, origin);
             }
+            ring_renderer.draw(&mut d3, &celestial_bodies, origin);
+            comets.draw(&mut d3, &celestial_bodies, origin);
 
             spaceship.draw(
                 &mut d3,
                 &ship_model,
-                camera_controller.camera.position,
-                camera_controller.camera.target,
+                camera_controller.position,
+                camera_controller.target,
             );
 
             if warp_effect.is_active() {
                 warp_effect.draw(&mut d3);
             }
+            // Se vuelve a planear cada frame: los cuerpos siguen moviéndose
+            if let Some(request) = &warp_preview {
+                let destination = request.destination(&celestial_bodies);
+                WarpPath::plan(origin, destination, &celestial_bodies, &collision_system).draw(&mut d3, origin);
+            }
         }
 
+        lagrange.draw_labels(&mut d, &render_camera, &celestial_bodies, &physics, origin);
         if show_info {
-            draw_ui(&mut d, &camera_controller, &celestial_bodies);
+            draw_ui(
+                &mut d,
+                &camera_controller,
+                &celestial_bodies,
+                &physics,
+                &trails,
+                &lagrange,
+                &influence,
+            );
+            // El panel de alineaciones ocupa el mismo lugar
+            if let Some(id) = celestial_bodies.nearest(camera_controller.position, |_| true).filter(|_| !alignments.open) {
+                draw_body_info(&mut d, &celestial_bodies, id);
+            }
+        }
+        draw_notice(&mut d, &notice);
+        if let Some(event) = &current_event {
+            draw_event_banner(&mut d, &celestial_bodies, event);
+        }
+        if let Some(request) = &warp_preview {
+            draw_warp_preview(&mut d, request);
+        }
+        alignments.draw(&mut d, &celestial_bodies, |t| event_time_label(&ephemeris, t));
+        let date = ephemeris.enabled.then(|| ephemeris.date_at(clock.time()));
+        draw_clock_hud(&mut d, &clock, date, &scales.label());
+        if let Some(text) = &date_prompt {
+            draw_date_prompt(&mut d, text);
+        }
+        if let Some(text) = &bookmark_prompt {
+            draw_bookmark_prompt(&mut d, text, &bookmarks);
         }
 
         d.draw_fps(10, 10);
     }
 }
 
-fn create_solar_system() -> Vec<CelestialBody> {
-    let mut bodies = vec![
-        CelestialBody::new_sun("Sol", 8.0, Color::new(255, 200, 50, 255)),
-        
-        CelestialBody::new_planet("Mercurio", 1.5, Color::GRAY, 15.0, 4.0, 2.0),
-        
-        {
-            let mut venus = CelestialBody::new_planet("Venus", 2.0, Color::ORANGE, 22.0, 3.5, 1.8);
-            venus.body_type = BodyType::GasGiant;
-            venus
-        },
-        
-        CelestialBody::new_planet("Tierra", 2.2, Color::BLUE, 30.0, 3.0, 1.5),
-        
-        CelestialBody::new_moon("Luna", 0.6, Color::LIGHTGRAY, 4.0, 8.0, 1.0, 3),
-        
-        {
-            let mut marte = CelestialBody::new_planet("Marte", 1.8, Color::RED, 40.0, 2.5, 1.4);
-            marte.body_type = BodyType::GasGiant;
-            marte
-        },
-        
-        CelestialBody::new_moon("Fobos", 0.3, Color::DARKGRAY, 3.5, 12.0, 2.0, 5),
-        
-        CelestialBody::new_moon("Deimos", 0.25, Color::GRAY, 5.0, 10.0, 1.5, 5),
-        
-        CelestialBody::new_planet("Jupiter", 5.0, Color::BROWN, 60.0, 1.3, 1.0),
-        
-        {
-            let mut saturno = CelestialBody::new_planet("Saturno", 4.5, Color::BEIGE, 80.0, 1.0, 0.9);
-            saturno.body_type = BodyType::GasGiant;
-            saturno
-        },
-        
-        CelestialBody::new_moon("Titan", 0.8, Color::ORANGE, 7.0, 6.0, 1.0, 9),
-        
-        CelestialBody::new_planet("Urano", 3.5, Color::SKYBLUE, 100.0, 0.7, 0.8),
-        
-        {
-            let mut neptuno = CelestialBody::new_planet("Neptuno", 3.5, Color::DARKBLUE, 120.0, 0.5, 0.7);
-            neptuno.body_type = BodyType::GasGiant;
-            neptuno
-        },
-    ];
-    
-    bodies
-}
-
 fn load_textures_for_bodies(rl: &mut RaylibHandle, thread: &RaylibThread, bodies: &mut [CelestialBody]) {
     let map: [(&str, &str); 13] = [
         ("Sol", "assets/textures/sol.png"),
@@ -303,73 +523,391 @@ fn load_textures_for_bodies(rl: &mut RaylibHandle, thread: &RaylibThread, bodies
     }
 }
 
-fn update_celestial_bodies(bodies: &mut Vec<CelestialBody>, delta_time: f32) {
-    let body_count = bodies.len();
-    
-    for i in 0..body_count {
-        let parent_pos = if let Some(parent_idx) = bodies[i].parent {
-            Some(bodies[parent_idx].position)
+/// Recalcula las posiciones de rieles sin avanzar el tiempo; en modo
+/// N-cuerpos también toma las velocidades de esas órbitas.
+fn place_bodies_on_rails(bodies: &mut BodyRegistry, physics: &PhysicsSystem) {
+    bodies.update_rails(0.0);
+    if physics.mode == PhysicsMode::NBody {
+        physics::sync_velocities_from_rails(bodies);
+    }
+}
+
+/// Edita el texto de la fecha. Devuelve la fecha juliana al confirmar con
+/// Enter; J cancela.
+fn handle_date_prompt(rl: &mut RaylibHandle, prompt: &mut Option<String>) -> Option<f64> {
+    let text = prompt.as_mut()?;
+    while let Some(c) = rl.get_char_pressed() {
+        if (c.is_ascii_digit() || c == '-' || c == ':' || c == ' ') && text.len() < 20 {
+            text.push(c);
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        text.pop();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_J) {
+        *prompt = None;
+        return None;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
+        let jd = ephemeris::parse_date(text);
+        // Si la fecha no es válida el texto queda abierto para corregirlo
+        if jd.is_some() {
+            *prompt = None;
+        }
+        return jd;
+    }
+    None
+}
+
+fn alt_down(rl: &RaylibHandle) -> bool {
+    rl.is_key_down(KeyboardKey::KEY_LEFT_ALT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_ALT)
+}
+
+/// Alt + 1-9 va al marcador de ese número y Alt + Shift + 1-9 guarda ahí la
+/// pose actual; Insert abre el marcador con nombre
+fn handle_bookmark_input(
+    rl: &mut RaylibHandle,
+    bookmarks: &mut Bookmarks,
+    prompt: &mut Option<String>,
+    camera_controller: &CameraController,
+    bodies: &BodyRegistry,
+    scale: &str,
+) -> Option<WarpRequest> {
+    if rl.is_key_pressed(KeyboardKey::KEY_INSERT) {
+        *prompt = Some(String::new());
+        while rl.get_char_pressed().is_some() {}
+        return None;
+    }
+    if !alt_down(rl) {
+        return None;
+    }
+    let (n, key) = WARP_KEYS.iter().enumerate().find(|(_, key)| rl.is_key_pressed(**key))?;
+    let name = (n + 1).to_string();
+    if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT) {
+        save_bookmark(bookmarks, &name, camera_controller, bodies, scale);
+        return None;
+    }
+    recall_bookmark(bookmarks, &name, *key, bodies, scale)
+}
+
+/// Edita el nombre del marcador. Enter va al marcador y Ctrl+Enter guarda
+/// ahí la pose actual; Insert cancela.
+fn handle_bookmark_prompt(
+    rl: &mut RaylibHandle,
+    prompt: &mut Option<String>,
+    bookmarks: &mut Bookmarks,
+    camera_controller: &CameraController,
+    bodies: &BodyRegistry,
+    scale: &str,
+) -> Option<WarpRequest> {
+    let text = prompt.as_mut()?;
+    while let Some(c) = rl.get_char_pressed() {
+        // La fuente de raylib no tiene acentos
+        if (c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_') && text.len() < 20 {
+            text.push(c);
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        text.pop();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_INSERT) {
+        *prompt = None;
+        return None;
+    }
+    if !(rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_KP_ENTER)) {
+        return None;
+    }
+    let name = text.trim().to_string();
+    if name.is_empty() {
+        return None;
+    }
+    if rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL) {
+        save_bookmark(bookmarks, &name, camera_controller, bodies, scale);
+        *prompt = None;
+        return None;
+    }
+    let request = recall_bookmark(bookmarks, &name, KeyboardKey::KEY_ENTER, bodies, scale);
+    // Si no existe el nombre queda abierto para corregirlo
+    if request.is_some() {
+        *prompt = None;
+    }
+    request
+}
+
+/// Guarda la pose y reescribe el archivo de marcadores
+fn save_bookmark(bookmarks: &mut Bookmarks, name: &str, camera_controller: &CameraController, bodies: &BodyRegistry, scale: &str) {
+    bookmarks.set(name, Bookmark::capture(camera_controller, bodies, scale));
+    match bookmarks.save(BOOKMARKS_PATH) {
+        Ok(()) => println!("✅ Marcador '{}' guardado en {}", name, BOOKMARKS_PATH),
+        Err(err) => println!("⚠️  No se pudo escribir {}: {}", BOOKMARKS_PATH, err),
+    }
+}
+
+fn recall_bookmark(
+    bookmarks: &Bookmarks,
+    name: &str,
+    key: KeyboardKey,
+    bodies: &BodyRegistry,
+    scale: &str,
+) -> Option<WarpRequest> {
+    let Some(bookmark) = bookmarks.get(name) else {
+        println!("⚠️  No hay un marcador '{}'", name);
+        return None;
+    };
+    // Las poses relativas a un cuerpo van en radios del cuerpo y sirven en
+    // cualquier escala; las fijas no
+    if bookmark.scale != scale && matches!(bookmark.pose, BookmarkPose::Fixed { .. }) {
+        println!("⚠️  El marcador '{}' se guardó en la escala {}", name, bookmark.scale);
+    }
+    let request = bookmark.warp_request(name, key, bodies);
+    if request.is_none() {
+        println!("⚠️  El cuerpo del marcador '{}' no está en la escena", name);
+    }
+    request
+}
+
+fn handle_clock_input(rl: &RaylibHandle, clock: &mut SimulationClock) {
+    if rl.is_key_pressed(KeyboardKey::KEY_P) {
+        clock.toggle_pause();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_T) {
+        clock.toggle_reverse();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+        clock.faster();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_MINUS) || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT) {
+        clock.slower();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+        clock.step();
+    }
+}
+
+fn handle_physics_input(
+    rl: &RaylibHandle,
+    physics: &mut PhysicsSystem,
+    bodies: &mut BodyRegistry,
+    scales: &ScaleSystem,
+    camera_pos: DVec3,
+    notice: &mut Notice,
+) {
+    // La gravedad y las velocidades están calibradas para las distancias
+    // artísticas
+    if rl.is_key_pressed(KeyboardKey::KEY_N) {
+        if scales.mode == ScaleMode::Artistic && !scales.is_animating() {
+            physics.toggle_mode(bodies);
         } else {
-            None
+            notice.warn("La física N-cuerpos solo funciona en la escala artística (F9)".to_string());
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_M) {
+        physics.toggle_integrator();
+    }
+
+    // X: quitar el cuerpo más cercano a la cámara (el Sol no se puede quitar)
+    if rl.is_key_pressed(KeyboardKey::KEY_X) {
+        let nearest = bodies.nearest(camera_pos, |b| !b.is_sun);
+        if let Some(id) = nearest {
+            bodies[id].active = false;
+        }
+    }
+    // Z: restaurar todos los cuerpos quitados
+    if rl.is_key_pressed(KeyboardKey::KEY_Z) {
+        for body in bodies.iter_mut() {
+            body.active = true;
+        }
+    }
+}
+
+/// F9/F10/F11: escala artística, logarítmica o real. Las órbitas de los
+/// rieles cambian de tamaño, así que no se permite en modo N-cuerpos.
+fn handle_scale_input(rl: &RaylibHandle, scales: &mut ScaleSystem, physics: &PhysicsSystem, notice: &mut Notice) {
+    let keys = [
+        (KeyboardKey::KEY_F9, ScaleMode::Artistic),
+        (KeyboardKey::KEY_F10, ScaleMode::Logarithmic),
+        (KeyboardKey::KEY_F11, ScaleMode::True),
+    ];
+    let Some(&(_, mode)) = keys.iter().find(|(key, _)| rl.is_key_pressed(*key)) else {
+        return;
+    };
+    if physics.mode != PhysicsMode::Rails {
+        notice.warn("La escala solo se cambia sobre los rieles: vuelve a rieles con N".to_string());
+        return;
+    }
+    if scales.is_animating() {
+        notice.warn("Espera a que termine el cambio de escala".to_string());
+        return;
+    }
+    if mode != scales.mode {
+        scales.set_mode(mode);
+        println!("✅ Escala {}", mode.label());
+    }
+}
+
+/// Ajusta a la escala actual lo que depende de las distancias: velocidad
+/// de la cámara, margen de colisión, tamaño del cielo, planos de recorte y
+/// colas de los cometas
+fn apply_view_scale(
+    scales: &ScaleSystem,
+    camera_controller: &mut CameraController,
+    collision_system: &mut CollisionSystem,
+    skybox: &mut Skybox,
+    comets: &mut CometSystem,
+    orbit_renderer: &mut OrbitRenderer,
+) {
+    camera_controller.move_speed = CAMERA_SPEED * scales.distance_factor_at(HOME_POSITION.length());
+    collision_system.safe_distance = SAFE_DISTANCE * scales.size_factor();
+    skybox.size = scales.distance_at(SKYBOX_SIZE);
+    comets.length_scale = scales.distance_factor_at(comet::ACTIVITY_RADIUS / 3.0);
+    orbit_renderer.length_scale = scales.size_factor();
+
+    // En la escala real los cuerpos miden centésimas de unidad y el cielo
+    // queda a cientos de miles: el plano cercano baja y el lejano sube
+    let near = (collision_system.safe_distance * 0.005).clamp(0.001, 0.01);
+    let far = skybox.size * 2.5;
+    set_clip_planes(near, far);
+}
+
+/// Planos de recorte de las proyecciones 3D; valen desde el próximo
+/// `begin_mode3D`
+fn set_clip_planes(near: f32, far: f32) {
+    // SAFETY: rlSetClipPlanes solo guarda los dos valores en variables
+    // globales de rlgl, sin punteros ni estado de OpenGL; se llama desde el
+    // hilo principal con la ventana ya creada
+    unsafe {
+        raylib::ffi::rlSetClipPlanes(near as f64, far as f64);
+    }
+}
+
+/// Pose fija llevada a la escala actual según su distancia al origen
+fn scaled_pose(scales: &ScaleSystem, pose: Vector3) -> DVec3 {
+    DVec3::from(pose) * scales.distance_factor_at(pose.length()) as f64
+}
+
+/// F alterna entre la cámara eclíptica y la libre (6 grados de libertad);
+/// Tab captura o suelta el mouse para mirar con él
+fn handle_camera_mode_input(rl: &mut RaylibHandle, camera_controller: &mut CameraController) {
+    if rl.is_key_pressed(KeyboardKey::KEY_F) {
+        camera_controller.toggle_mode();
+        println!("✅ Cámara {}", camera_controller.mode.label());
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+        camera_controller.mouse_captured = !camera_controller.mouse_captured;
+        if camera_controller.mouse_captured {
+            rl.disable_cursor();
+        } else {
+            rl.enable_cursor();
+        }
+    }
+}
+
+/// Inicio sigue al cuerpo más cercano al centro de la vista (o lo suelta),
+/// RePag/AvPag pasan al cuerpo anterior/siguiente del registro y Fin elige
+/// si los warps a un cuerpo terminan acompañándolo
+fn handle_tracking_input(
+    rl: &RaylibHandle,
+    camera_controller: &mut CameraController,
+    bodies: &BodyRegistry,
+    top_down: &mut bool,
+) {
+    if rl.is_key_pressed(KeyboardKey::KEY_END) {
+        camera_controller.co_move = !camera_controller.co_move;
+        let label = if camera_controller.co_move { "acompañan al cuerpo" } else { "se quedan quietos" };
+        println!("✅ Al llegar, los warps {}", label);
+    }
+    let selected = if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+        if camera_controller.tracking.take().is_some() {
+            println!("✅ Cámara suelta");
+            return;
+        }
+        body_in_view(bodies, camera_controller)
+    } else if let Some(current) = camera_controller.tracking {
+        let step = if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) {
+            1
+        } else if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP) {
+            -1
+        } else {
+            return;
         };
-        
-        bodies[i].update(delta_time, parent_pos);
+        let active: Vec<BodyId> = bodies.ids().filter(|&id| bodies[id].active).collect();
+        let index = active.iter().position(|&id| id == current).unwrap_or(0) as isize;
+        active.get((index + step).rem_euclid(active.len() as isize) as usize).copied()
+    } else {
+        return;
+    };
+
+    if let Some(id) = selected {
+        *top_down = false;
+        camera_controller.track(id, bodies[id].position);
+        println!("✅ Orbitando {}", bodies.path(id));
+    }
+}
+
+/// Dónde estará un cuerpo dentro de `lead` segundos de simulación: sobre
+/// los rieles se resuelve su órbita y en N-cuerpos se extrapola la velocidad
+fn predict_position(bodies: &BodyRegistry, physics: &PhysicsSystem, id: BodyId, lead: f64) -> DVec3 {
+    match physics.mode {
+        PhysicsMode::Rails => bodies.rails_positions(lead)[id],
+        PhysicsMode::NBody => bodies[id].position + bodies[id].velocity * lead as f32,
     }
 }
 
+/// Cuerpo activo más cercano al centro de la pantalla, descontando su
+/// radio aparente para que un planeta grande gane a un punto detrás
+fn body_in_view(bodies: &BodyRegistry, camera: &CameraController) -> Option<BodyId> {
+    let forward = camera.forward();
+    let off_center = |id: BodyId| {
+        let body = &bodies[id];
+        let to_body = body.position - camera.position;
+        let distance = to_body.length() as f32;
+        let angle = forward.dot(to_body.to_f32() / distance).clamp(-1.0, 1.0).acos();
+        angle - (body.radius / distance).min(1.0).asin()
+    };
+    bodies
+        .ids()
+        .filter(|&id| bodies[id].active && (bodies[id].position - camera.position).length() > 1e-6)
+        .min_by(|&a, &b| off_center(a).total_cmp(&off_center(b)))
+}
+
+/// Devuelve el warp pedido con 0-9; lo inicia `start_planned_warp`
 fn handle_input(
     rl: &RaylibHandle,
     camera_controller: &mut CameraController,
-    bodies: &[CelestialBody],
-    warp_effect: &mut WarpEffect,
+    bodies: &BodyRegistry,
+    scales: &ScaleSystem,
     spaceship: &mut Spaceship,
     top_down: &mut bool,
-) {
+) -> Option<WarpRequest> {
+    let mut request = None;
     if rl.is_key_pressed(KeyboardKey::KEY_ZERO) {
-        let target = Vector3::new(0.0, 20.0, 40.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
+        let factor = scales.distance_factor_at(OVERVIEW_POSITION.length());
+        request = Some(WarpRequest {
+            key: KeyboardKey::KEY_ZERO,
+            label: "vista general".to_string(),
+            body: None,
+            point: scaled_pose(scales, OVERVIEW_POSITION),
+            offset: WARP_OFFSET * factor,
+            fovy: None,
+        });
     }
     
-    if rl.is_key_pressed(KeyboardKey::KEY_ONE) && bodies.len() > 1 {
-        let target = bodies[1].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_TWO) && bodies.len() > 2 {
-        let target = bodies[2].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_THREE) && bodies.len() > 3 {
-        let target = bodies[3].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_FOUR) && bodies.len() > 5 {
-        let target = bodies[5].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_FIVE) && bodies.len() > 8 {
-        let target = bodies[8].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_SIX) && bodies.len() > 9 {
-        let target = bodies[9].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_SEVEN) && bodies.len() > 11 {
-        let target = bodies[11].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
-    }
-    if rl.is_key_pressed(KeyboardKey::KEY_EIGHT) && bodies.len() > 12 {
-        let target = bodies[12].position + Vector3::new(0.0, 5.0, 15.0);
-        camera_controller.start_warp(target);
-        warp_effect.start(camera_controller.camera.position, target);
+    // 1-9: planetas de la estrella principal en el orden del escenario. La
+    // distancia de llegada depende del radio, así Júpiter y Mercurio se
+    // ven de un tamaño parecido
+    let planets = bodies.planets();
+    for (key, id) in WARP_KEYS.iter().zip(planets) {
+        if rl.is_key_pressed(*key) {
+            let margin = SAFE_DISTANCE * scales.size_factor();
+            request = Some(WarpRequest {
+                key: *key,
+                label: bodies[id].name.clone(),
+                body: Some(id),
+                point: bodies[id].position,
+                offset: WARP_OFFSET.normalized() * (bodies[id].radius * ARRIVAL_RADII + margin),
+                fovy: None,
+            });
+        }
     }
 
     if rl.is_key_pressed(KeyboardKey::KEY_V) {
@@ -378,16 +916,212 @@ fn handle_input(
 
     if rl.is_key_pressed(KeyboardKey::KEY_B) {
         *top_down = !*top_down;
+        camera_controller.tracking = None;
     }
 
     if rl.is_key_pressed(KeyboardKey::KEY_R) {
-        let home_pos = Vector3::new(0.0, 30.0, 50.0);
-        camera_controller.camera.position = home_pos;
-        camera_controller.camera.target = Vector3::zero();
+        camera_controller.tracking = None;
+        let home_pos = scaled_pose(scales, HOME_POSITION);
+        camera_controller.position = home_pos;
+        camera_controller.target = DVec3::zero();
+    }
+    request
+}
+
+/// Planea el camino del warp alrededor de los cuerpos y lo inicia
+fn start_planned_warp(
+    camera_controller: &mut CameraController,
+    warp_effect: &mut WarpEffect,
+    bodies: &BodyRegistry,
+    collision_system: &CollisionSystem,
+    request: &WarpRequest,
+) {
+    let path = WarpPath::plan(camera_controller.position, request.destination(bodies), bodies, collision_system);
+    if path.detours() > 0 {
+        println!("✅ Warp a {}: rodeando {} cuerpo(s)", request.label, path.detours());
+    }
+    warp_effect.start(camera_controller.position, path.end());
+    match request.body {
+        Some(id) => camera_controller.warp_to_body(id, path, request.offset),
+        None => camera_controller.start_warp(path, request.offset),
+    }
+    if let Some(fovy) = request.fovy {
+        camera_controller.warp_fovy_to(fovy);
+    }
+}
+
+const LAGRANGE_KEYS: [KeyboardKey; 5] = [
+    KeyboardKey::KEY_F1,
+    KeyboardKey::KEY_F2,
+    KeyboardKey::KEY_F3,
+    KeyboardKey::KEY_F4,
+    KeyboardKey::KEY_F5,
+];
+
+/// G muestra los puntos de Lagrange empezando por el par del cuerpo más
+/// cercano, H pasa al siguiente par y F1-F5 piden un warp a L1-L5 del par
+fn handle_lagrange_input(
+    rl: &RaylibHandle,
+    lagrange: &mut LagrangeSystem,
+    bodies: &BodyRegistry,
+    physics: &PhysicsSystem,
+    scales: &ScaleSystem,
+    camera_controller: &CameraController,
+) -> Option<WarpRequest> {
+    if rl.is_key_pressed(KeyboardKey::KEY_G) {
+        lagrange.enabled = !lagrange.enabled;
+        let nearest = bodies
+            .nearest(camera_controller.position, |b| !b.is_sun)
+            .filter(|_| lagrange.enabled);
+        if let Some(id) = nearest {
+            lagrange.select_body(bodies, id);
+        }
+    }
+    if !lagrange.enabled {
+        return None;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_H) {
+        lagrange.select_next(bodies);
+    }
+
+    let pair = lagrange.selected(bodies)?;
+    let points = pair.points(bodies, physics)?;
+    let offset = WARP_OFFSET * scales.radius_factor(bodies, pair.secondary);
+    let (n, (key, point)) = LAGRANGE_KEYS.iter().zip(points).enumerate().find(|(_, (key, _))| rl.is_key_pressed(**key))?;
+    Some(WarpRequest {
+        key: *key,
+        label: format!("L{} {}", n + 1, pair.label(bodies)),
+        body: None,
+        point: point + offset,
+        offset,
+        fovy: None,
+    })
+}
+
+/// F6/F7 buscan el evento anterior/siguiente para saltar a él y F8 imprime
+/// en consola los eventos alrededor del instante actual. Las predicciones
+/// usan los rieles, así que en modo N-cuerpos no se buscan.
+fn handle_event_input(
+    rl: &RaylibHandle,
+    bodies: &BodyRegistry,
+    physics: &PhysicsSystem,
+    ephemeris: &Ephemeris,
+    now: f64,
+    notice: &mut Notice,
+) -> Option<Event> {
+    let keys = [KeyboardKey::KEY_F6, KeyboardKey::KEY_F7, KeyboardKey::KEY_F8];
+    if !keys.iter().any(|&key| rl.is_key_pressed(key)) {
+        return None;
+    }
+    if physics.mode != PhysicsMode::Rails {
+        notice.warn("Los eventos se predicen sobre los rieles: vuelve a rieles con N".to_string());
+        return None;
+    }
+
+    if rl.is_key_pressed(KeyboardKey::KEY_F8) {
+        let list = events::find_events(bodies, now, now - events::SEARCH_HORIZON, now + events::SEARCH_HORIZON);
+        println!("=== {} eventos ===", list.len());
+        for event in &list {
+            println!("  {}  {}", event_time_label(ephemeris, event.peak), event.describe(bodies));
+        }
+    }
+    let found = if rl.is_key_pressed(KeyboardKey::KEY_F6) {
+        events::previous_event(bodies, now)
+    } else if rl.is_key_pressed(KeyboardKey::KEY_F7) {
+        events::next_event(bodies, now)
+    } else {
+        return None;
+    };
+    if found.is_none() {
+        notice.warn(format!("No hay eventos en {:.0} s de simulación", events::SEARCH_HORIZON));
+    }
+    found
+}
+
+/// C abre la lista de alineaciones desde ahora; con la lista abierta
+/// Arriba/Abajo navegan, Q cambia de observador, Y exporta a CSV y Enter
+/// devuelve la alineación a la que saltar
+fn handle_alignment_input(
+    rl: &RaylibHandle,
+    alignments: &mut AlignmentBrowser,
+    bodies: &BodyRegistry,
+    physics: &PhysicsSystem,
+    ephemeris: &Ephemeris,
+    now: f64,
+    notice: &mut Notice,
+) -> Option<Alignment> {
+    if rl.is_key_pressed(KeyboardKey::KEY_C) {
+        if alignments.open {
+            alignments.open = false;
+        } else if physics.mode != PhysicsMode::Rails {
+            notice.warn("Las alineaciones se predicen sobre los rieles: vuelve a rieles con N".to_string());
+        } else {
+            alignments.open = true;
+            alignments.search(bodies, now);
+        }
+    }
+    if !alignments.open {
+        return None;
+    }
+
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+        alignments.select_previous();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+        alignments.select_next();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_Q) {
+        alignments.next_observer(bodies, now);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_Y) {
+        let csv = alignments.to_csv(bodies, |t| ephemeris.enabled.then(|| ephemeris::format_date(ephemeris.date_at(t))));
+        match std::fs::write(alignment::CSV_PATH, csv) {
+            Ok(()) => println!("✅ {} alineaciones exportadas a {}", alignments.results.len(), alignment::CSV_PATH),
+            Err(err) => println!("⚠️  No se pudo escribir {}: {}", alignment::CSV_PATH, err),
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
+        return alignments.current().cloned();
+    }
+    None
+}
+
+/// Lleva el reloj y los rieles a otro instante (un evento, una alineación)
+/// y deja el reloj en pausa. Las estelas se borran porque los cuerpos saltan.
+fn jump_rails_to(
+    time: f64,
+    clock: &mut SimulationClock,
+    bodies: &mut BodyRegistry,
+    belts: &mut [Belt],
+    trails: &mut TrailSystem,
+) {
+    let jump = (time - clock.time()) as f32;
+    clock.jump_to(time);
+    bodies.update_rails(jump);
+    for belt in belts.iter_mut() {
+        belt.update(jump);
+    }
+    trails.clear();
+}
+
+/// Fecha del calendario si las efemérides están activas; si no, tiempo del reloj
+fn event_time_label(ephemeris: &Ephemeris, sim_time: f64) -> String {
+    if ephemeris.enabled {
+        ephemeris::format_date(ephemeris.date_at(sim_time))
+    } else {
+        format!("t = {:.1} s", sim_time)
     }
 }
 
-fn draw_ui(d: &mut RaylibDrawHandle, camera: &CameraController, _bodies: &[CelestialBody]) {
+fn draw_ui(
+    d: &mut RaylibDrawHandle,
+    camera: &CameraController,
+    bodies: &BodyRegistry,
+    physics: &PhysicsSystem,
+    trails: &TrailSystem,
+    lagrange: &LagrangeSystem,
+    influence: &InfluenceOverlay,
+) {
     let y_start = 40;
     let line_height = 20;
 
@@ -395,26 +1129,168 @@ fn draw_ui(d: &mut RaylibDrawHandle, camera: &CameraController, _bodies: &[Celes
     d.draw_text("WASD: Mover", 10, y_start + line_height, 16, Color::LIGHTGRAY);
     d.draw_text("Flechas: Rotar camara", 10, y_start + line_height * 2, 16, Color::LIGHTGRAY);
     d.draw_text("Espacio/Shift: Arriba/Abajo", 10, y_start + line_height * 3, 16, Color::LIGHTGRAY);
-    d.draw_text("0-8: Warp a planetas", 10, y_start + line_height * 4, 16, Color::LIGHTGRAY);
+    let warp_count = bodies.planets().len().min(WARP_KEYS.len());
+    d.draw_text(&format!("0-{}: Warp a planetas (Ctrl: vista previa del camino)", warp_count), 10, y_start + line_height * 4, 16, Color::LIGHTGRAY);
     d.draw_text("O: Toggle orbitas", 10, y_start + line_height * 5, 16, Color::LIGHTGRAY);
     d.draw_text("I: Toggle info", 10, y_start + line_height * 6, 16, Color::LIGHTGRAY);
     d.draw_text("V: Toggle orbita nave", 10, y_start + line_height * 7, 16, Color::LIGHTGRAY);
     d.draw_text("B: Vista cenital", 10, y_start + line_height * 8, 16, Color::LIGHTGRAY);
     d.draw_text("B R: Ir a inicio", 10, y_start + line_height * 9, 16, Color::LIGHTGRAY);
+    d.draw_text("N/M: Fisica N-cuerpos / integrador", 10, y_start + line_height * 10, 16, Color::LIGHTGRAY);
+    d.draw_text("X/Z: Quitar cuerpo cercano / restaurar", 10, y_start + line_height * 11, 16, Color::LIGHTGRAY);
+    d.draw_text("P/T/.: Pausa / reversa / paso   +/-: Velocidad", 10, y_start + line_height * 12, 16, Color::LIGHTGRAY);
+    d.draw_text("E: Efemerides   J: Saltar a fecha", 10, y_start + line_height * 13, 16, Color::LIGHTGRAY);
+    d.draw_text("L/K: Estelas / marco de estelas", 10, y_start + line_height * 14, 16, Color::LIGHTGRAY);
+    d.draw_text("G/H/F1-F5: Lagrange / siguiente par / warp", 10, y_start + line_height * 15, 16, Color::LIGHTGRAY);
+    d.draw_text("U: Esferas de Hill / influencia", 10, y_start + line_height * 16, 16, Color::LIGHTGRAY);
+    d.draw_text("F6/F7/F8: Evento anterior / siguiente / lista", 10, y_start + line_height * 17, 16, Color::LIGHTGRAY);
+    d.draw_text("C: Alineaciones y conjunciones", 10, y_start + line_height * 18, 16, Color::LIGHTGRAY);
+    d.draw_text("F9/F10/F11: Escala artistica / logaritmica / real", 10, y_start + line_height * 19, 16, Color::LIGHTGRAY);
+    d.draw_text("F/Tab: Camara libre / mirar con mouse (boton der.: roll)", 10, y_start + line_height * 20, 16, Color::LIGHTGRAY);
+    d.draw_text("Inicio/RePag/AvPag/Fin: Orbitar cuerpo / acompanar tras warp", 10, y_start + line_height * 21, 16, Color::LIGHTGRAY);
+    d.draw_text("Alt+1-9 / Alt+Shift+1-9 / Insert: Ir a / guardar / nombrar marcador", 10, y_start + line_height * 22, 16, Color::LIGHTGRAY);
 
     // Información de posición
     d.draw_text(
         &format!(
-            "Posicion: X:{:.1} Y:{:.1} Z:{:.1}",
-            camera.camera.position.x,
-            camera.camera.position.y,
-            camera.camera.position.z
+            "Posicion: X:{:.1} Y:{:.1} Z:{:.1}   Camara: {}{}{}   Warp: {}",
+            camera.position.x,
+            camera.position.y,
+            camera.position.z,
+            camera.mode.label(),
+            if camera.mouse_captured { " (mouse)" } else { "" },
+            camera.tracking.map_or(String::new(), |id| format!(", orbitando {}", bodies[id].name)),
+            if camera.co_move { "acompana al llegar" } else { "queda quieto" }
         ),
         10,
-        y_start + line_height * 11,
+        y_start + line_height * 23,
+        16,
+        Color::YELLOW,
+    );
+    d.draw_text(
+        &format!("Fisica: {}", physics.mode_label()),
+        10,
+        y_start + line_height * 24,
         16,
         Color::YELLOW,
     );
+    if let Some(id) = bodies.nearest(camera.position, |_| true) {
+        d.draw_text(
+            &format!("Cerca de: {}", bodies.path(id)),
+            10,
+            y_start + line_height * 25,
+            16,
+            Color::YELLOW,
+        );
+    }
+    if trails.enabled {
+        d.draw_text(
+            &format!("Estelas: {}", trails.frame.label()),
+            10,
+            y_start + line_height * 26,
+            16,
+            Color::YELLOW,
+        );
+    }
+    if let Some(pair) = lagrange.selected(bodies).filter(|_| lagrange.enabled) {
+        d.draw_text(
+            &format!("Lagrange: {}", pair.label(bodies)),
+            10,
+            y_start + line_height * 27,
+            16,
+            Color::YELLOW,
+        );
+    }
+    if influence.enabled {
+        d.draw_text(
+            &format!("Hill (cian) / SOI (magenta): {} lunas con problemas", influence.report.len()),
+            10,
+            y_start + line_height * 28,
+            16,
+            Color::YELLOW,
+        );
+    }
+}
+
+// Aviso en curso, bajo las líneas de estado del panel de información;
+// visible aunque la info esté oculta para que un rechazo no pase inadvertido
+fn draw_notice(d: &mut RaylibDrawHandle, notice: &Notice) {
+    if let Some(text) = notice.hud_text() {
+        d.draw_text(&text, 10, SCREEN_HEIGHT - 100, 16, Color::ORANGE);
+    }
+}
+
+// Ficha física del cuerpo más cercano, a la derecha bajo el reloj
+fn draw_body_info(d: &mut RaylibDrawHandle, bodies: &BodyRegistry, id: BodyId) {
+    let body = &bodies[id];
+    let lines = PhysicalProperties::of(bodies, id).lines(&body.physical);
+    if lines.is_empty() {
+        return;
+    }
+    let (x, y, line_height) = (SCREEN_WIDTH - 450, 104, 20);
+    d.draw_rectangle(x - 10, y - 8, 450, 36 + lines.len() as i32 * line_height, Color::new(0, 0, 0, 160));
+    d.draw_text(&format!("=== {} ===", body.name.to_uppercase()), x, y, 18, Color::WHITE);
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, x, y + 26 + i as i32 * line_height, 16, Color::LIGHTGRAY);
+    }
+}
+
+// Indicador del reloj, visible aunque la info esté oculta
+fn draw_clock_hud(d: &mut RaylibDrawHandle, clock: &SimulationClock, date: Option<f64>, scale: &str) {
+    // Amarillo mientras el tope de N-cuerpos frena el multiplicador elegido
+    let color = if clock.paused {
+        Color::ORANGE
+    } else if clock.is_capped() {
+        Color::YELLOW
+    } else {
+        Color::GREEN
+    };
+    d.draw_text(&clock.status_label(), SCREEN_WIDTH - 200, 10, 20, color);
+    d.draw_text(
+        &format!("t = {:.1} s", clock.time()),
+        SCREEN_WIDTH - 200,
+        34,
+        16,
+        Color::LIGHTGRAY,
+    );
+    if let Some(jd) = date {
+        d.draw_text(&ephemeris::format_date(jd), SCREEN_WIDTH - 200, 54, 16, Color::SKYBLUE);
+    }
+    d.draw_text(&format!("Escala: {}", scale), SCREEN_WIDTH - 200, 74, 16, Color::LIGHTGRAY);
+}
+
+// Evento en curso, centrado abajo mientras el reloj esté dentro de él
+fn draw_event_banner(d: &mut RaylibDrawHandle, bodies: &BodyRegistry, event: &Event) {
+    let text = event.describe(bodies);
+    let width = d.measure_text(&text, 20);
+    d.draw_text(&text, (SCREEN_WIDTH - width) / 2, SCREEN_HEIGHT - 40, 20, Color::SKYBLUE);
+}
+
+fn draw_bookmark_prompt(d: &mut RaylibDrawHandle, text: &str, bookmarks: &Bookmarks) {
+    let x = SCREEN_WIDTH / 2 - 260;
+    let y = SCREEN_HEIGHT / 2 - 55;
+    d.draw_rectangle(x, y, 520, 110, Color::new(0, 0, 0, 200));
+    d.draw_rectangle_lines(x, y, 520, 110, Color::SKYBLUE);
+    d.draw_text("Marcador (Enter: ir   Ctrl+Enter: guardar)", x + 12, y + 10, 18, Color::SKYBLUE);
+    d.draw_text(&format!("{}_", text), x + 12, y + 44, 20, Color::WHITE);
+    let names: Vec<&str> = bookmarks.names().take(10).collect();
+    let saved = if names.is_empty() { "ninguno".to_string() } else { names.join(", ") };
+    d.draw_text(&format!("Guardados: {}", saved), x + 12, y + 80, 16, Color::LIGHTGRAY);
+}
+
+fn draw_warp_preview(d: &mut RaylibDrawHandle, request: &WarpRequest) {
+    let text = format!("Warp a {}: repite la tecla para ir, Retroceso cancela", request.label);
+    let width = d.measure_text(&text, 20);
+    d.draw_text(&text, (SCREEN_WIDTH - width) / 2, SCREEN_HEIGHT - 70, 20, Color::new(120, 255, 200, 255));
+}
+
+fn draw_date_prompt(d: &mut RaylibDrawHandle, text: &str) {
+    let x = SCREEN_WIDTH / 2 - 220;
+    let y = SCREEN_HEIGHT / 2 - 40;
+    d.draw_rectangle(x, y, 440, 80, Color::new(0, 0, 0, 200));
+    d.draw_rectangle_lines(x, y, 440, 80, Color::SKYBLUE);
+    d.draw_text("Saltar a fecha (AAAA-MM-DD HH:MM)", x + 12, y + 10, 18, Color::SKYBLUE);
+    d.draw_text(&format!("{}_", text), x + 12, y + 44, 20, Color::WHITE);
 }
 
 // (Shader params removed; using procedural CPU textures instead)
\ No newline at end of file
@@ -1,44 +1,161 @@
 use raylib::prelude::*;
+use std::f32::consts::PI;
+
+use crate::celestial_body::CelestialBody;
+use crate::floating_origin::DVec3;
+
+const PERIAPSIS_COLOR: Color = Color::new(120, 200, 255, 200);
+const APOAPSIS_COLOR: Color = Color::new(255, 170, 90, 200);
+const NODE_COLOR: Color = Color::new(140, 255, 140, 200);
+
+const MIN_SEGMENTS: i32 = 24;
+const MAX_SEGMENTS: i32 = 1024;
+/// Distancia de la cámara, en semiejes mayores, a la que una órbita empieza
+/// a desvanecerse y a la que desaparece
+const FADE_START: f32 = 25.0;
+const FADE_END: f32 = 80.0;
+/// Opacidad relativa de una órbita vista de canto
+const EDGE_ON_ALPHA: f32 = 0.35;
+/// Semieje mayor mínimo que se dibuja en la escala artística; en las otras
+/// acompaña al factor de tamaño para que Fobos y Deimos no desaparezcan
+const MIN_SEMI_MAJOR_AXIS: f32 = 0.1;
 
 pub struct OrbitRenderer {
-    pub segments: i32,
+    camera_position: Vector3,
+    /// Píxeles que ocupa una unidad de la escena a distancia 1 de la cámara
+    focal_length: f32,
+    /// Factor de tamaño de la escala activa (ver `ScaleSystem::size_factor`)
+    pub length_scale: f32,
 }
 
 impl OrbitRenderer {
     pub fn new() -> Self {
-        Self { segments: 100 }
+        Self {
+            camera_position: Vector3::zero(),
+            focal_length: 1.0,
+            length_scale: 1.0,
+        }
     }
 
-    pub fn draw_orbit<D: RaylibDraw3D>(
-        &self,
-        d: &mut D,
-        center: Vector3,
-        radius: f32,
-        color: Color,
-    ) {
-        if radius < 0.1 {
+    /// Toma la cámara del frame; el nivel de detalle y el desvanecido
+    /// dependen de ella
+    pub fn set_view(&mut self, camera: &Camera3D, screen_height: i32) {
+        self.camera_position = camera.position;
+        self.focal_length = screen_height as f32 * 0.5 / (camera.fovy.to_radians() * 0.5).tan();
+    }
+
+    /// Dibuja la elipse completa de un cuerpo. `center` es la posición
+    /// actual del foco (el padre), así que las órbitas de las lunas acompañan
+    /// a su planeta. La línea parte del cuerpo y se apaga hacia adelante, de
+    /// modo que el tramo más brillante es el que acaba de recorrer. Los
+    /// puntos se calculan en `f64` y se pasan relativos a `origin`: en la
+    /// escala real, lejos del Sol, `f32` no alcanza para una órbita lunar.
+    pub fn draw_orbit<D: RaylibDraw3D>(&self, d: &mut D, center: DVec3, origin: DVec3, body: &CelestialBody) {
+        let orbit = &body.orbit;
+        if self.too_small(body) {
+            return;
+        }
+        let local = center.relative_to(origin);
+        let visibility = self.visibility(local, body);
+        if visibility <= 0.0 {
             return;
         }
 
-        let angle_step = 360.0 / self.segments as f32;
-        
-        for i in 0..self.segments {
-            let angle1 = (i as f32 * angle_step).to_radians();
-            let angle2 = ((i + 1) as f32 * angle_step).to_radians();
+        let segments = self.segments_for(local, orbit.semi_major_axis);
+        let direction = if body.orbital_speed < 0.0 { -1.0 } else { 1.0 };
+        let step = direction * std::f64::consts::TAU / segments as f64;
+        let start = orbit.precise_eccentric_anomaly(body.orbital_angle.to_radians());
+        let point = |i: i32| {
+            (center + orbit.position_at_eccentric_anomaly(start - i as f64 * step).rotate_by(body.orbit_frame)).relative_to(origin)
+        };
 
-            let point1 = Vector3::new(
-                center.x + radius * angle1.cos(),
-                center.y,
-                center.z + radius * angle1.sin(),
-            );
+        let mut previous = point(0);
+        for i in 1..=segments {
+            let current = point(i);
+            let behind = 1.0 - i as f32 / segments as f32;
+            d.draw_line_3D(previous, current, shade(body.orbit_color, visibility, behind * behind));
+            previous = current;
+        }
+    }
 
-            let point2 = Vector3::new(
-                center.x + radius * angle2.cos(),
-                center.y,
-                center.z + radius * angle2.sin(),
-            );
+    /// Periapsis y apoapsis como puntos, y una marca perpendicular al plano
+    /// de referencia en el nodo ascendente. En órbitas casi circulares o casi
+    /// planas esas referencias no están definidas y se omiten.
+    pub fn draw_markers<D: RaylibDraw3D>(&self, d: &mut D, center: DVec3, origin: DVec3, body: &CelestialBody) {
+        let orbit = &body.orbit;
+        if self.too_small(body) {
+            return;
+        }
+        let visibility = self.visibility(center.relative_to(origin), body);
+        if visibility <= 0.0 {
+            return;
+        }
+        let frame = body.orbit_frame;
+        let size = (orbit.semi_major_axis * 0.012).max(0.05 * self.length_scale).min(0.8);
+        let at = |true_anomaly: f64| (center + orbit.position_at_true_anomaly(true_anomaly).rotate_by(frame)).relative_to(origin);
 
-            d.draw_line_3D(point1, point2, color);
+        if orbit.eccentricity > 0.01 {
+            d.draw_sphere_ex(at(0.0), size, 4, 6, fade(PERIAPSIS_COLOR, visibility));
+            d.draw_sphere_ex(at(180.0), size, 4, 6, fade(APOAPSIS_COLOR, visibility));
         }
+
+        // El nodo ascendente es donde el cuerpo cruza el plano de referencia
+        // hacia +Y: argumento de latitud ω + ν = 0
+        let inclination = orbit.inclination.rem_euclid(180.0);
+        if inclination > 0.5 && inclination < 179.5 {
+            let node = at(-orbit.arg_periapsis as f64);
+            let up = Vector3::new(0.0, 1.0, 0.0).rotate_by(frame) * (size * 4.0);
+            d.draw_line_3D(node - up, node + up, fade(NODE_COLOR, visibility));
+        }
+    }
+
+    /// Órbitas degeneradas (o de cuerpos en el centro de su padre), relativo
+    /// a la escala activa
+    fn too_small(&self, body: &CelestialBody) -> bool {
+        body.orbit.semi_major_axis < MIN_SEMI_MAJOR_AXIS * self.length_scale
+    }
+
+    /// Segmentos para que el error de cuerda, a·(1 - cos(π/N)) ≈ a·π²/2N²,
+    /// quede por debajo de medio píxel. El radio en pantalla se estima con
+    /// la distancia al tramo más cercano de la órbita, no al centro, para
+    /// que una órbita grande vista de cerca siga siendo suave.
+    fn segments_for(&self, center: Vector3, semi_major_axis: f32) -> i32 {
+        let distance = (self.camera_position - center).length();
+        let nearest = (distance - semi_major_axis).abs().max(semi_major_axis * 0.02);
+        let radius_px = semi_major_axis * self.focal_length / nearest;
+        ((PI * radius_px.sqrt()) as i32).clamp(MIN_SEGMENTS, MAX_SEGMENTS)
+    }
+
+    /// Opacidad de 0 a 1: baja con la distancia medida en semiejes mayores
+    /// (las órbitas de las lunas se apagan antes que las de los planetas) y
+    /// cuando el plano de la órbita se ve de canto
+    fn visibility(&self, center: Vector3, body: &CelestialBody) -> f32 {
+        let a = body.orbit.semi_major_axis;
+        let offset = center - self.camera_position;
+        let distance = offset.length();
+        let distance_fade = ((FADE_END * a - distance) / ((FADE_END - FADE_START) * a)).clamp(0.0, 1.0);
+        if distance < 1e-3 {
+            return distance_fade;
+        }
+
+        let normal = Vector3::new(0.0, 1.0, 0.0).rotate_by(body.orbit_frame * body.orbit.plane_rotation());
+        let facing = normal.dot(offset / distance).abs();
+        distance_fade * (EDGE_ON_ALPHA + (1.0 - EDGE_ON_ALPHA) * facing)
     }
-}
\ No newline at end of file
+}
+
+/// Color del tramo: `highlight` va de 0 (delante del cuerpo) a 1 (justo
+/// detrás) y aclara el color además de hacerlo más opaco
+fn shade(color: Color, visibility: f32, highlight: f32) -> Color {
+    let boost = 0.8 + 0.5 * highlight;
+    Color::new(
+        (color.r as f32 * boost).min(255.0) as u8,
+        (color.g as f32 * boost).min(255.0) as u8,
+        (color.b as f32 * boost).min(255.0) as u8,
+        (color.a as f32 * visibility * (0.4 + 1.2 * highlight)).min(255.0) as u8,
+    )
+}
+
+fn fade(color: Color, visibility: f32) -> Color {
+    Color::new(color.r, color.g, color.b, (color.a as f32 * visibility) as u8)
+}
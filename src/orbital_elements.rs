@@ -0,0 +1,206 @@
+use raylib::prelude::*;
+
+use crate::floating_origin::DVec3;
+
+/// Excentricidad máxima: con e >= 1 la órbita no es cerrada y cerca de 1 la
+/// ecuación de Kepler deja de converger bien
+pub const MAX_ECCENTRICITY: f32 = 0.99;
+
+/// Elementos keplerianos de una órbita. Los ángulos se guardan en grados,
+/// igual que `orbital_angle` en `CelestialBody`.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub ascending_node: f32,
+    pub arg_periapsis: f32,
+    pub mean_anomaly_epoch: f32,
+}
+
+impl OrbitalElements {
+    pub fn new(
+        semi_major_axis: f32,
+        eccentricity: f32,
+        inclination: f32,
+        ascending_node: f32,
+        arg_periapsis: f32,
+        mean_anomaly_epoch: f32,
+    ) -> Self {
+        Self {
+            semi_major_axis,
+            eccentricity: eccentricity.clamp(0.0, MAX_ECCENTRICITY),
+            inclination,
+            ascending_node,
+            arg_periapsis,
+            mean_anomaly_epoch,
+        }
+    }
+
+    /// Órbita circular en el plano eclíptico (caso e = 0, i = 0)
+    pub fn circular(radius: f32) -> Self {
+        Self::new(radius, 0.0, 0.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn periapsis(&self) -> f32 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    pub fn apoapsis(&self) -> f32 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Resuelve la ecuación de Kepler M = E - e·sin(E) por Newton-Raphson.
+    /// Recibe y devuelve radianes.
+    pub fn eccentric_anomaly(&self, mean_anomaly: f32) -> f32 {
+        self.precise_eccentric_anomaly(mean_anomaly as f64) as f32
+    }
+
+    pub fn precise_eccentric_anomaly(&self, mean_anomaly: f64) -> f64 {
+        let e = self.eccentricity as f64;
+        let m = mean_anomaly.rem_euclid(std::f64::consts::TAU);
+        if e < 1e-6 {
+            return m;
+        }
+
+        // Para excentricidades altas E0 = π converge mejor que E0 = M
+        let mut ecc = if e > 0.8 { std::f64::consts::PI } else { m };
+        for _ in 0..30 {
+            let f = ecc - e * ecc.sin() - m;
+            let df = 1.0 - e * ecc.cos();
+            let step = f / df;
+            ecc -= step;
+            if step.abs() < 1e-12 {
+                break;
+            }
+        }
+        ecc
+    }
+
+    /// Anomalía verdadera (radianes) a partir de la anomalía media (radianes)
+    pub fn true_anomaly(&self, mean_anomaly: f32) -> f32 {
+        let e = self.eccentricity;
+        let ecc = self.eccentric_anomaly(mean_anomaly);
+        let half = ((1.0 + e) / (1.0 - e)).sqrt() * (ecc * 0.5).tan();
+        2.0 * half.atan()
+    }
+
+    /// Posición relativa al foco para una anomalía media en grados.
+    /// El plano de referencia es XZ con Y hacia arriba, así que con
+    /// e = 0 e i = 0 se obtiene el mismo círculo que antes. Va en doble
+    /// precisión: en la escala real un semieje de 30 000 unidades en `f32`
+    /// avanza a saltos de milésimas.
+    pub fn precise_position_at(&self, mean_anomaly_deg: f64) -> DVec3 {
+        self.position_at_eccentric_anomaly(self.precise_eccentric_anomaly(mean_anomaly_deg.to_radians()))
+    }
+
+    /// Posición relativa al foco para una anomalía excéntrica en radianes.
+    /// Muestrear E uniforme reparte los puntos de la elipse mejor que M o ν.
+    pub fn position_at_eccentric_anomaly(&self, ecc: f64) -> DVec3 {
+        let (a, e) = (self.semi_major_axis as f64, self.eccentricity as f64);
+
+        // Coordenadas en el plano de la órbita (periapsis sobre el eje x)
+        let px = a * (ecc.cos() - e);
+        let py = a * (1.0 - e * e).sqrt() * ecc.sin();
+
+        self.to_reference_plane(px, py)
+    }
+
+    /// Posición relativa al foco para una anomalía verdadera en grados
+    pub fn position_at_true_anomaly(&self, true_anomaly_deg: f64) -> DVec3 {
+        let (a, e) = (self.semi_major_axis as f64, self.eccentricity as f64);
+        let nu = true_anomaly_deg.to_radians();
+        let r = a * (1.0 - e * e) / (1.0 + e * nu.cos());
+        self.to_reference_plane(r * nu.cos(), r * nu.sin())
+    }
+
+    /// Velocidad kepleriana relativa al foco para una anomalía media en grados,
+    /// dado el parámetro gravitacional `mu` = G·(M + m)
+    pub fn velocity_at(&self, mean_anomaly_deg: f32, mu: f32) -> Vector3 {
+        let e = self.eccentricity;
+        let nu = self.true_anomaly(mean_anomaly_deg.to_radians());
+        let p = self.semi_major_axis * (1.0 - e * e);
+        if p <= 0.0 || mu <= 0.0 {
+            return Vector3::zero();
+        }
+        let k = (mu / p).sqrt();
+        self.to_reference_plane((-k * nu.sin()) as f64, (k * (e + nu.cos())) as f64).to_f32()
+    }
+
+    /// Rotación que lleva el plano de referencia XZ al plano de la órbita
+    /// (i alrededor de X y luego Ω alrededor de Y). La misma que aplica
+    /// `to_reference_plane` sin el argumento del periapsis.
+    pub fn plane_rotation(&self) -> Quaternion {
+        let node = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), -self.ascending_node.to_radians());
+        let tilt = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), -self.inclination.to_radians());
+        node * tilt
+    }
+
+    /// Rota un punto del plano orbital por ω, i y Ω hacia el plano XZ
+    fn to_reference_plane(self, px: f64, py: f64) -> DVec3 {
+        let (sin_w, cos_w) = (self.arg_periapsis as f64).to_radians().sin_cos();
+        let (sin_i, cos_i) = (self.inclination as f64).to_radians().sin_cos();
+        let (sin_o, cos_o) = (self.ascending_node as f64).to_radians().sin_cos();
+
+        let xw = px * cos_w - py * sin_w;
+        let yw = px * sin_w + py * cos_w;
+
+        DVec3::new(
+            xw * cos_o - yw * cos_i * sin_o,
+            yw * sin_i,
+            xw * sin_o + yw * cos_i * cos_o,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn kepler_residual(orbit: &OrbitalElements, m: f32) -> f32 {
+        let ecc = orbit.eccentric_anomaly(m);
+        ecc - orbit.eccentricity * ecc.sin() - m
+    }
+
+    #[test]
+    fn anomalia_excentrica_circular() {
+        let orbit = OrbitalElements::circular(10.0);
+        for m in [0.0, 0.5, PI, 5.0] {
+            assert!((orbit.eccentric_anomaly(m) - m).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn anomalia_excentrica_muy_excentrica() {
+        let orbit = OrbitalElements::new(10.0, 0.9, 0.0, 0.0, 0.0, 0.0);
+        assert!(orbit.eccentric_anomaly(0.0).abs() < 1e-6);
+        assert!((orbit.eccentric_anomaly(PI) - PI).abs() < 1e-5);
+        // Cerca del periapsis Newton converge mal si se parte de E0 = M
+        for m in [1e-4, 0.01, 0.1, PI - 0.01, PI + 0.01, 2.0 * PI - 0.01] {
+            assert!(kepler_residual(&orbit, m).abs() < 1e-5, "M = {}", m);
+        }
+    }
+
+    #[test]
+    fn residuo_de_kepler() {
+        for e in [0.0, 0.1, 0.5, 0.7, 0.9, 0.99] {
+            let orbit = OrbitalElements::new(1.0, e, 0.0, 0.0, 0.0, 0.0);
+            for k in 0..36 {
+                let m = k as f32 * 10.0_f32.to_radians();
+                assert!(kepler_residual(&orbit, m).abs() < 1e-5, "e = {}, M = {}", e, m);
+            }
+        }
+    }
+
+    #[test]
+    fn distancias_de_periapsis_y_apoapsis() {
+        let orbit = OrbitalElements::new(20.0, 0.4, 30.0, 60.0, 45.0, 0.0);
+        let periapsis = orbit.precise_position_at(0.0).length();
+        let apoapsis = orbit.precise_position_at(180.0).length();
+        assert!((periapsis - 20.0 * (1.0 - 0.4)).abs() < 1e-4);
+        assert!((apoapsis - 20.0 * (1.0 + 0.4)).abs() < 1e-4);
+        assert!((periapsis - orbit.periapsis() as f64).abs() < 1e-4);
+        assert!((apoapsis - orbit.apoapsis() as f64).abs() < 1e-4);
+    }
+}
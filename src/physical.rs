@@ -0,0 +1,163 @@
+use crate::body_registry::{BodyId, BodyRegistry};
+
+/// Constante de gravitación en unidades SI (m³ / kg·s²). La de la
+/// simulación (`physics::GRAVITATIONAL_CONSTANT`) está en unidades de escena.
+const G_SI: f64 = 6.674_30e-11;
+const SOLAR_MASS_KG: f64 = 1.988_47e30;
+const EARTH_MASS_KG: f64 = 5.972_2e24;
+const METERS_PER_AU: f64 = 1.495_978_707e11;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Datos físicos que no se pueden derivar de la masa, el radio y la órbita;
+/// todos opcionales en el escenario
+#[derive(Debug, Clone, Default)]
+pub struct PhysicalData {
+    /// Día sidéreo en horas
+    pub sidereal_day: Option<f32>,
+    /// Albedo de Bond (fracción de la luz reflejada)
+    pub albedo: Option<f32>,
+    /// Temperatura media en la superficie (o a 1 bar en los gigantes) en K
+    pub temperature: Option<f32>,
+    /// (gas, % en volumen), de mayor a menor
+    pub atmosphere: Vec<(String, f32)>,
+}
+
+/// Propiedades físicas de un cuerpo en unidades reales. Las que faltan no
+/// tienen datos suficientes: la densidad, la gravedad y la velocidad de
+/// escape necesitan masa y `true_radius`; el periodo, `true_semi_major_axis`.
+#[derive(Debug, Clone, Default)]
+pub struct PhysicalProperties {
+    pub mass_kg: Option<f64>,
+    /// g/cm³
+    pub density: Option<f64>,
+    /// m/s²
+    pub surface_gravity: Option<f64>,
+    /// km/s
+    pub escape_velocity: Option<f64>,
+    /// Periodo sidéreo real en días, por la tercera ley de Kepler
+    pub orbital_period: Option<f64>,
+    /// Periodo en segundos de simulación (360° / `orbital_speed`)
+    pub sim_period: Option<f64>,
+    /// (K, verdadero si es la temperatura de equilibrio calculada)
+    pub temperature: Option<(f64, bool)>,
+}
+
+impl PhysicalProperties {
+    pub fn of(bodies: &BodyRegistry, id: BodyId) -> Self {
+        let body = &bodies[id];
+        let mass_kg = (body.mass > 0.0).then_some(body.mass as f64 * SOLAR_MASS_KG);
+        let radius_m = body.true_radius.map(|km| km as f64 * 1000.0);
+
+        let mut props = Self {
+            mass_kg,
+            temperature: body.physical.temperature.map(|t| (t as f64, false)),
+            ..Self::default()
+        };
+        if let (Some(m), Some(r)) = (mass_kg, radius_m) {
+            let volume = 4.0 / 3.0 * std::f64::consts::PI * r.powi(3);
+            // kg/m³ a g/cm³
+            props.density = Some(m / volume / 1000.0);
+            props.surface_gravity = Some(G_SI * m / (r * r));
+            props.escape_velocity = Some((2.0 * G_SI * m / r).sqrt() / 1000.0);
+        }
+
+        if !body.is_sun {
+            let central = body.parent.or(bodies.primary()).filter(|&c| c != id);
+            let mu = central.map_or(0.0, |c| G_SI * (bodies[c].mass + body.mass) as f64 * SOLAR_MASS_KG);
+            if let (Some(au), true) = (body.true_semi_major_axis, mu > 0.0) {
+                let a = au as f64 * METERS_PER_AU;
+                let seconds = std::f64::consts::TAU * (a.powi(3) / mu).sqrt();
+                props.orbital_period = Some(seconds / SECONDS_PER_DAY);
+            }
+            if body.orbital_speed.abs() > 1e-6 {
+                props.sim_period = Some(360.0 / body.orbital_speed.abs() as f64);
+            }
+            if props.temperature.is_none() {
+                props.temperature = equilibrium_temperature(bodies, id).map(|t| (t, true));
+            }
+        }
+        props
+    }
+
+    /// Líneas para el panel de información (sin acentos: la fuente de
+    /// raylib no los tiene)
+    pub fn lines(&self, data: &PhysicalData) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(m) = self.mass_kg {
+            lines.push(format!("Masa: {:.3e} kg ({} Tierras)", m, number(m / EARTH_MASS_KG)));
+        }
+        if let Some(density) = self.density {
+            lines.push(format!("Densidad media: {:.2} g/cm3", density));
+        }
+        if let Some(g) = self.surface_gravity {
+            lines.push(format!("Gravedad superficial: {} m/s2 ({} g)", number(g), number(g / 9.80665)));
+        }
+        if let Some(v) = self.escape_velocity {
+            lines.push(format!("Velocidad de escape: {} km/s", number(v)));
+        }
+        if let Some(hours) = data.sidereal_day {
+            lines.push(format!("Dia sidereo: {}", format_duration(hours as f64 / 24.0)));
+        }
+        match (self.orbital_period, self.sim_period) {
+            (Some(days), Some(sim)) => lines.push(format!("Periodo orbital: {} ({:.1} s sim)", format_duration(days), sim)),
+            (Some(days), None) => lines.push(format!("Periodo orbital: {}", format_duration(days))),
+            (None, Some(sim)) => lines.push(format!("Periodo orbital: {:.1} s sim", sim)),
+            (None, None) => {}
+        }
+        if let Some(albedo) = data.albedo {
+            lines.push(format!("Albedo: {:.3}", albedo));
+        }
+        match self.temperature {
+            Some((k, false)) => lines.push(format!("Temperatura media: {:.0} K ({:.0} C)", k, k - 273.15)),
+            Some((k, true)) => lines.push(format!("Temperatura de equilibrio: {:.0} K ({:.0} C)", k, k - 273.15)),
+            None => {}
+        }
+        if !data.atmosphere.is_empty() {
+            let gases: Vec<String> = data
+                .atmosphere
+                .iter()
+                .map(|(gas, percent)| format!("{} {}%", gas, percent))
+                .collect();
+            lines.push(format!("Atmosfera: {}", gases.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Temperatura de equilibrio T = T★·√(R★ / 2d)·(1 - A)^¼, con d la distancia
+/// media a la estrella (la del planeta, para una luna). Necesita el albedo
+/// del cuerpo y la temperatura y el radio real de la estrella.
+fn equilibrium_temperature(bodies: &BodyRegistry, id: BodyId) -> Option<f64> {
+    let albedo = bodies[id].physical.albedo? as f64;
+    let star = &bodies[bodies.star_of(id)?];
+    let star_temperature = star.physical.temperature? as f64;
+    let star_radius = star.true_radius? as f64 * 1000.0;
+
+    let orbiter = std::iter::once(id)
+        .chain(bodies.ancestors(id))
+        .find(|&b| bodies[b].parent.is_none_or(|p| bodies[p].is_sun))
+        .filter(|&b| !bodies[b].is_sun)?;
+    let distance = bodies[orbiter].true_semi_major_axis? as f64 * METERS_PER_AU;
+
+    Some(star_temperature * (star_radius / (2.0 * distance)).sqrt() * (1.0 - albedo).powf(0.25))
+}
+
+/// Días como horas, días o años según el orden de magnitud
+fn format_duration(days: f64) -> String {
+    if days < 2.0 {
+        format!("{:.2} h", days * 24.0)
+    } else if days < 730.0 {
+        format!("{:.2} d", days)
+    } else {
+        format!("{:.2} a", days / 365.25)
+    }
+}
+
+/// Dos decimales, o notación científica si se perderían (lunas y cometas)
+fn number(x: f64) -> String {
+    if x != 0.0 && x.abs() < 0.01 {
+        format!("{:.2e}", x)
+    } else {
+        format!("{:.2}", x)
+    }
+}
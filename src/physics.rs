@@ -0,0 +1,266 @@
+use raylib::prelude::*;
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::celestial_body::CelestialBody;
+use crate::floating_origin::DVec3;
+
+/// Constante gravitacional en unidades de la simulación (masa solar = 1).
+/// Con este valor la Tierra conserva su periodo de rieles (3°/s a 30 unidades).
+pub const GRAVITATIONAL_CONSTANT: f32 = 74.0;
+
+// Suavizado para que dos cuerpos muy cercanos no generen aceleraciones infinitas
+const SOFTENING: f32 = 0.05;
+// Paso máximo por sub-iteración; los frames largos se dividen en varios pasos
+const MAX_SUBSTEP: f32 = 1.0 / 120.0;
+// Tope de sub-pasos por frame: con multiplicadores de tiempo altos el paso
+// crece en lugar de congelar la aplicación (y la precisión baja)
+const MAX_SUBSTEPS_PER_FRAME: usize = 2000;
+/// Multiplicador de tiempo máximo en N-cuerpos: a 60 fps un frame de 1000x
+/// son justo 2000 sub-pasos de 1/120 s. Más rápido el paso crecería y las
+/// órbitas de las lunas (de decenas de segundos) se desarmarían.
+pub const MAX_TIME_SCALE: f64 = 1_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicsMode {
+    Rails,
+    NBody,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    Leapfrog,
+    Rk4,
+}
+
+pub struct PhysicsSystem {
+    pub mode: PhysicsMode,
+    pub integrator: Integrator,
+}
+
+impl PhysicsSystem {
+    pub fn new() -> Self {
+        Self {
+            mode: PhysicsMode::Rails,
+            integrator: Integrator::Leapfrog,
+        }
+    }
+
+    /// Alterna entre rieles y N-cuerpos. Al entrar en N-cuerpos se parte de
+    /// las posiciones y velocidades actuales de los rieles; al volver los
+    /// cuerpos saltan a donde los rieles están en ese instante, porque sus
+    /// ángulos siguen avanzando durante N-cuerpos.
+    pub fn toggle_mode(&mut self, bodies: &mut BodyRegistry) {
+        self.mode = match self.mode {
+            PhysicsMode::Rails => {
+                sync_velocities_from_rails(bodies);
+                PhysicsMode::NBody
+            }
+            PhysicsMode::NBody => PhysicsMode::Rails,
+        };
+    }
+
+    pub fn toggle_integrator(&mut self) {
+        self.integrator = match self.integrator {
+            Integrator::Leapfrog => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::Leapfrog,
+        };
+    }
+
+    pub fn mode_label(&self) -> &'static str {
+        match self.mode {
+            PhysicsMode::Rails => "Rieles",
+            PhysicsMode::NBody => match self.integrator {
+                Integrator::Leapfrog => "N-cuerpos (Leapfrog)",
+                Integrator::Rk4 => "N-cuerpos (RK4)",
+            },
+        }
+    }
+
+    /// Velocidad de `id` respecto a `reference`, su padre (o la estrella
+    /// que orbita si no tiene): en rieles la de su elipse y en N-cuerpos la
+    /// integrada, porque ahí los ángulos de los rieles no son su movimiento
+    pub fn relative_velocity(&self, bodies: &BodyRegistry, id: BodyId, reference: BodyId) -> Vector3 {
+        match self.mode {
+            PhysicsMode::Rails => rail_velocity(&bodies[id]),
+            PhysicsMode::NBody => bodies[id].velocity - bodies[reference].velocity,
+        }
+    }
+
+    /// Avanza el sistema bajo gravedad mutua. Los cuerpos inactivos no
+    /// atraen ni se mueven.
+    pub fn step(&self, bodies: &mut [CelestialBody], delta_time: f32) {
+        if delta_time == 0.0 {
+            return;
+        }
+        let substeps = ((delta_time.abs() / MAX_SUBSTEP).ceil() as usize).clamp(1, MAX_SUBSTEPS_PER_FRAME);
+        let dt = delta_time / substeps as f32;
+
+        let masses: Vec<f32> = bodies
+            .iter()
+            .map(|b| if b.active { b.mass } else { 0.0 })
+            .collect();
+        // La gravedad solo corre en la escala artística, donde `f32` alcanza
+        let mut positions: Vec<Vector3> = bodies.iter().map(|b| b.position.to_f32()).collect();
+        let mut velocities: Vec<Vector3> = bodies.iter().map(|b| b.velocity).collect();
+
+        for _ in 0..substeps {
+            match self.integrator {
+                Integrator::Leapfrog => leapfrog_step(&mut positions, &mut velocities, &masses, dt),
+                Integrator::Rk4 => rk4_step(&mut positions, &mut velocities, &masses, dt),
+            }
+        }
+
+        for (i, body) in bodies.iter_mut().enumerate() {
+            if body.active {
+                body.position = DVec3::from(positions[i]);
+                body.velocity = velocities[i];
+            }
+        }
+    }
+}
+
+/// Toma como velocidad de cada cuerpo la de su movimiento sobre los rieles
+/// (la derivada de `position_at` por `orbital_speed`), sumando la del padre.
+/// Se recorre el árbol en orden topológico para que cada padre ya tenga su
+/// velocidad cuando se resuelven sus hijos.
+pub fn sync_velocities_from_rails(bodies: &mut BodyRegistry) {
+    let primary = bodies.primary();
+
+    for k in 0..bodies.update_order().len() {
+        let id = bodies.update_order()[k];
+        if bodies[id].is_sun {
+            bodies[id].velocity = Vector3::zero();
+            continue;
+        }
+
+        let parent_velocity = bodies[id].parent.map_or(Vector3::zero(), |p| bodies[p].velocity);
+        bodies[id].velocity = parent_velocity + rail_velocity(&bodies[id]);
+    }
+
+    // La estrella principal compensa el momento total para que el sistema
+    // no derive
+    if let Some(s) = primary {
+        let mut momentum = Vector3::zero();
+        for (id, body) in bodies.ids().zip(bodies.iter()) {
+            if id != s && body.active {
+                momentum += body.velocity * body.mass;
+            }
+        }
+        if bodies[s].mass > 0.0 {
+            bodies[s].velocity = momentum * (-1.0 / bodies[s].mass);
+        }
+    }
+}
+
+/// Velocidad relativa al padre sobre los rieles. El movimiento medio de los
+/// rieles es `orbital_speed`; es la velocidad kepleriana con μ = n²·a³, el
+/// que tendría un cuerpo con ese periodo, así que no hace falta derivar
+/// numéricamente.
+fn rail_velocity(body: &CelestialBody) -> Vector3 {
+    let n = body.orbital_speed.to_radians();
+    let mu = n * n * body.orbit.semi_major_axis.powi(3);
+    let velocity = body.orbit.velocity_at(body.orbital_angle as f32, mu) * n.signum();
+    velocity.rotate_by(body.orbit_frame)
+}
+
+fn accelerations(positions: &[Vector3], masses: &[f32]) -> Vec<Vector3> {
+    let mut acc = vec![Vector3::zero(); positions.len()];
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            if masses[i] <= 0.0 && masses[j] <= 0.0 {
+                continue;
+            }
+            let delta = positions[j] - positions[i];
+            let dist_sq = delta.dot(delta) + SOFTENING * SOFTENING;
+            let inv_dist3 = 1.0 / (dist_sq * dist_sq.sqrt());
+            let f = delta * (GRAVITATIONAL_CONSTANT * inv_dist3);
+            acc[i] += f * masses[j];
+            acc[j] -= f * masses[i];
+        }
+    }
+    acc
+}
+
+/// Leapfrog kick-drift-kick: simpléctico y reversible en el tiempo
+fn leapfrog_step(positions: &mut [Vector3], velocities: &mut [Vector3], masses: &[f32], dt: f32) {
+    let acc = accelerations(positions, masses);
+    for i in 0..positions.len() {
+        velocities[i] += acc[i] * (dt * 0.5);
+        positions[i] += velocities[i] * dt;
+    }
+    let acc = accelerations(positions, masses);
+    for i in 0..positions.len() {
+        velocities[i] += acc[i] * (dt * 0.5);
+    }
+}
+
+/// Runge-Kutta clásico de cuarto orden sobre (posición, velocidad)
+fn rk4_step(positions: &mut [Vector3], velocities: &mut [Vector3], masses: &[f32], dt: f32) {
+    let n = positions.len();
+    let offset = |base: &[Vector3], k: &[Vector3], h: f32| -> Vec<Vector3> {
+        (0..n).map(|i| base[i] + k[i] * h).collect()
+    };
+
+    let k1_x: Vec<Vector3> = velocities.to_vec();
+    let k1_v = accelerations(positions, masses);
+
+    let x2 = offset(positions, &k1_x, dt * 0.5);
+    let k2_x = offset(velocities, &k1_v, dt * 0.5);
+    let k2_v = accelerations(&x2, masses);
+
+    let x3 = offset(positions, &k2_x, dt * 0.5);
+    let k3_x = offset(velocities, &k2_v, dt * 0.5);
+    let k3_v = accelerations(&x3, masses);
+
+    let x4 = offset(positions, &k3_x, dt);
+    let k4_x = offset(velocities, &k3_v, dt);
+    let k4_v = accelerations(&x4, masses);
+
+    for i in 0..n {
+        positions[i] += (k1_x[i] + (k2_x[i] + k3_x[i]) * 2.0 + k4_x[i]) * (dt / 6.0);
+        velocities[i] += (k1_v[i] + (k2_v[i] + k3_v[i]) * 2.0 + k4_v[i]) * (dt / 6.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RADIUS: f32 = 30.0;
+
+    /// Estrella de masa 1 en el origen y un planeta sin masa en órbita
+    /// circular; devuelve el radio y la energía específica tras una vuelta
+    fn one_orbit(step: fn(&mut [Vector3], &mut [Vector3], &[f32], f32)) -> [(f32, f32); 2] {
+        let speed = (GRAVITATIONAL_CONSTANT / RADIUS).sqrt();
+        let mut positions = vec![Vector3::zero(), Vector3::new(RADIUS, 0.0, 0.0)];
+        let mut velocities = vec![Vector3::zero(), Vector3::new(0.0, 0.0, speed)];
+        let masses = [1.0, 0.0];
+        let energy = |p: &[Vector3], v: &[Vector3]| {
+            let r = (p[1] - p[0]).length();
+            (r, 0.5 * (v[1] - v[0]).dot(v[1] - v[0]) - GRAVITATIONAL_CONSTANT / r)
+        };
+
+        let start = energy(&positions, &velocities);
+        let period = std::f32::consts::TAU * RADIUS / speed;
+        let steps = (period / MAX_SUBSTEP).ceil() as usize;
+        for _ in 0..steps {
+            step(&mut positions, &mut velocities, &masses, period / steps as f32);
+        }
+        [start, energy(&positions, &velocities)]
+    }
+
+    fn assert_keeps_orbit(step: fn(&mut [Vector3], &mut [Vector3], &[f32], f32)) {
+        let [(r0, e0), (r1, e1)] = one_orbit(step);
+        assert!((r1 - r0).abs() / r0 < 1e-3, "el radio pasó de {} a {}", r0, r1);
+        assert!((e1 - e0).abs() / e0.abs() < 1e-3, "la energía pasó de {} a {}", e0, e1);
+    }
+
+    #[test]
+    fn leapfrog_conserva_la_orbita_circular() {
+        assert_keeps_orbit(leapfrog_step);
+    }
+
+    #[test]
+    fn rk4_conserva_la_orbita_circular() {
+        assert_keeps_orbit(rk4_step);
+    }
+}
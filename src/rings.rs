@@ -0,0 +1,177 @@
+use raylib::prelude::*;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+
+/// Muestras del perfil radial; el perfil se sube como textura de 1 px de alto
+pub const PROFILE_SIZE: usize = 512;
+/// Segmentos angulares del anillo
+const RING_SEGMENTS: i32 = 256;
+
+/// Sistema de anillos de un cuerpo. El color y la opacidad solo dependen de
+/// la distancia al centro: `profile[0]` es el borde interior y el último
+/// elemento el exterior.
+#[derive(Debug, Clone)]
+pub struct RingSystem {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub profile: Vec<Color>,
+}
+
+/// Banda del perfil procedural, en unidades de la escena
+#[derive(Debug, Clone, Copy)]
+pub struct RingBand {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub color: Color,
+}
+
+impl RingSystem {
+    /// Perfil procedural: cada banda aporta su color y encima se agregan
+    /// variaciones finas de brillo y opacidad (los "anillitos")
+    pub fn from_bands(inner_radius: f32, outer_radius: f32, bands: &[RingBand], gaps: &[(f32, f32)]) -> Self {
+        let profile = (0..PROFILE_SIZE)
+            .map(|i| {
+                let r = sample_radius(inner_radius, outer_radius, i);
+                let Some(band) = bands.iter().rev().find(|b| (b.inner_radius..=b.outer_radius).contains(&r)) else {
+                    return Color::BLANK;
+                };
+                let ringlets = (r * 37.0).sin() * (r * 11.3).sin();
+                let brightness = 1.0 + 0.12 * ringlets;
+                let opacity = 1.0 + 0.2 * (r * 23.0).sin() * ringlets;
+                Color::new(
+                    (band.color.r as f32 * brightness).clamp(0.0, 255.0) as u8,
+                    (band.color.g as f32 * brightness).clamp(0.0, 255.0) as u8,
+                    (band.color.b as f32 * brightness).clamp(0.0, 255.0) as u8,
+                    (band.color.a as f32 * opacity).clamp(0.0, 255.0) as u8,
+                )
+            })
+            .collect();
+        Self { inner_radius, outer_radius, profile }.with_gaps(gaps)
+    }
+
+    /// Perfil tomado de una imagen: la fila central, de izquierda (borde
+    /// interior) a derecha (borde exterior)
+    pub fn from_image(inner_radius: f32, outer_radius: f32, image: &mut Image, gaps: &[(f32, f32)]) -> Self {
+        let (width, row) = (image.width(), image.height() / 2);
+        let profile = (0..PROFILE_SIZE)
+            .map(|i| {
+                let x = (i * width as usize / PROFILE_SIZE) as i32;
+                image.get_color(x, row)
+            })
+            .collect();
+        Self { inner_radius, outer_radius, profile }.with_gaps(gaps)
+    }
+
+    /// Las divisiones (Cassini, Encke, ...) quedan transparentes
+    fn with_gaps(mut self, gaps: &[(f32, f32)]) -> Self {
+        for (i, color) in self.profile.iter_mut().enumerate() {
+            let r = sample_radius(self.inner_radius, self.outer_radius, i);
+            if gaps.iter().any(|&(from, to)| (from..=to).contains(&r)) {
+                *color = Color::BLANK;
+            }
+        }
+        self
+    }
+}
+
+fn sample_radius(inner_radius: f32, outer_radius: f32, i: usize) -> f32 {
+    let t = (i as f32 + 0.5) / PROFILE_SIZE as f32;
+    inner_radius + (outer_radius - inner_radius) * t
+}
+
+// La malla es un plano cuyas coordenadas de textura se convierten en radio
+// (u) y ángulo (v), así una sola malla sirve para cualquier par de radios.
+const RING_VS: &str = r#"#version 330
+in vec3 vertexPosition;
+in vec2 vertexTexCoord;
+uniform mat4 mvp;
+uniform vec2 radii;
+out vec2 fragTexCoord;
+void main() {
+    float r = mix(radii.x, radii.y, vertexTexCoord.x);
+    float phi = vertexTexCoord.y * 6.2831853;
+    fragTexCoord = vertexTexCoord;
+    gl_Position = mvp * vec4(r * cos(phi), 0.0, r * sin(phi), 1.0);
+}
+"#;
+
+// Los texeles transparentes se descartan para que las divisiones no
+// escriban profundidad y tapen lo que hay detrás
+const RING_FS: &str = r#"#version 330
+in vec2 fragTexCoord;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+out vec4 finalColor;
+void main() {
+    vec4 color = texture(texture0, vec2(fragTexCoord.x, 0.5)) * colDiffuse;
+    if (color.a < 0.01) discard;
+    finalColor = color;
+}
+"#;
+
+/// Dibuja todos los anillos con una malla compartida y una textura de
+/// perfil por cuerpo
+pub struct RingRenderer {
+    mesh: Mesh,
+    material: WeakMaterial,
+    radii_loc: i32,
+    textures: Vec<(BodyId, Texture2D)>,
+}
+
+impl RingRenderer {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread, bodies: &BodyRegistry) -> Self {
+        let shader = rl.load_shader_from_memory(thread, Some(RING_VS), Some(RING_FS));
+        let radii_loc = shader.get_shader_location("radii");
+
+        let mut material = rl.load_material_default(thread);
+        // El material se queda con el shader durante toda la ejecución
+        *material.shader_mut() = unsafe { shader.make_weak() };
+
+        let mut textures = Vec::new();
+        for id in bodies.ids() {
+            let Some(ring) = &bodies[id].ring else { continue };
+            let mut image = Image::gen_image_color(PROFILE_SIZE as i32, 1, Color::BLANK);
+            for (x, color) in ring.profile.iter().enumerate() {
+                image.draw_pixel(x as i32, 0, *color);
+            }
+            let texture = rl
+                .load_texture_from_image(thread, &image)
+                .expect("No se pudo crear la textura del anillo");
+            texture.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+            texture.set_texture_wrap(thread, TextureWrap::TEXTURE_WRAP_CLAMP);
+            textures.push((id, texture));
+        }
+
+        Self {
+            mesh: Mesh::gen_mesh_plane(thread, 1.0, 1.0, 1, RING_SEGMENTS),
+            material,
+            radii_loc,
+            textures,
+        }
+    }
+
+    /// Cada anillo se dibuja dos veces, la segunda volteada, para verlo por
+    /// ambas caras con el descarte de caras traseras activo. El plano sigue
+    /// el ecuador del cuerpo, relativo a la cámara (`origin`).
+    pub fn draw<D: RaylibDraw3D>(&mut self, d: &mut D, bodies: &BodyRegistry, origin: DVec3) {
+        let flip = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), std::f32::consts::PI);
+
+        for (id, texture) in &self.textures {
+            let body = &bodies[*id];
+            let Some(ring) = body.ring.as_ref().filter(|_| body.active) else { continue };
+
+            self.material
+                .shader_mut()
+                .set_shader_value(self.radii_loc, Vector2::new(ring.inner_radius, ring.outer_radius));
+            self.material.set_material_texture(MaterialMapIndex::MATERIAL_MAP_ALBEDO, texture);
+
+            let equator = body.equator_rotation();
+            let center = body.position.relative_to(origin);
+            let translate = Matrix::translate(center.x, center.y, center.z);
+            for side in [equator, equator * flip] {
+                d.draw_mesh(&self.mesh, self.material.clone(), side.to_matrix() * translate);
+            }
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use crate::belt::Belt;
+use crate::body_registry::{BodyId, BodyRegistry};
+
+/// Unidades de escena por UA en la escala real (1 unidad ≈ 149 600 km)
+pub const UNITS_PER_AU: f32 = 1000.0;
+const KM_PER_AU: f32 = 149_597_870.7;
+/// Escala logarítmica de distancias: d = LOG_DISTANCE·log10(1 + a / LOG_DISTANCE_KNEE), con a en UA
+const LOG_DISTANCE: f32 = 80.0;
+const LOG_DISTANCE_KNEE: f32 = 0.3;
+/// Escala logarítmica de radios: r = LOG_RADIUS·log10(1 + R / LOG_RADIUS_KNEE), con R en km
+const LOG_RADIUS: f32 = 2.0;
+const LOG_RADIUS_KNEE: f32 = 100.0;
+/// Segundos de pantalla que dura el paso de una escala a otra
+const TRANSITION_SECONDS: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Los valores del escenario, elegidos a mano
+    Artistic,
+    /// Distancias y radios reales comprimidos con un logaritmo
+    Logarithmic,
+    /// Distancias en UA y radios en km a la misma escala
+    True,
+}
+
+impl ScaleMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleMode::Artistic => "artistica",
+            ScaleMode::Logarithmic => "logaritmica",
+            ScaleMode::True => "real",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            ScaleMode::Artistic => 0,
+            ScaleMode::Logarithmic => 1,
+            ScaleMode::True => 2,
+        }
+    }
+}
+
+/// Semieje mayor y radio de un cuerpo en una escala
+#[derive(Debug, Clone, Copy)]
+struct Size {
+    semi_major_axis: f32,
+    radius: f32,
+}
+
+struct BodyScale {
+    id: BodyId,
+    /// Indexado por `ScaleMode::index`
+    sizes: [Size; 3],
+    /// Anillos en la escala artística; escalan con el radio del cuerpo
+    ring: Option<(f32, f32)>,
+}
+
+/// Escalas de presentación del sistema. Cambiar de escala interpola semiejes
+/// y radios en escala logarítmica durante `TRANSITION_SECONDS`, así el paso
+/// de la artística a la real no es un salto. Solo cambian tamaños: los
+/// periodos (grados por segundo) se conservan.
+pub struct ScaleSystem {
+    pub mode: ScaleMode,
+    from: ScaleMode,
+    progress: f32,
+    bodies: Vec<BodyScale>,
+    /// (semieje artístico, semieje en la escala) de los planetas con datos
+    /// reales, ordenado; reubica cinturones y cuerpos sin datos
+    curves: [Vec<(f32, f32)>; 3],
+    /// Radio en la escala / radio artístico, promedio geométrico
+    size_factors: [f32; 3],
+}
+
+impl ScaleSystem {
+    /// Calcula los tamaños de cada cuerpo en las tres escalas. Un cuerpo sin
+    /// `true_radius` toma el factor de tamaño promedio; uno sin
+    /// `true_semi_major_axis` se ubica interpolando entre los que tienen
+    /// datos (o, si es una luna, escalando con su planeta).
+    pub fn new(bodies: &BodyRegistry) -> Self {
+        let modes = [ScaleMode::Logarithmic, ScaleMode::True];
+        let mut sizes: HashMap<BodyId, [Size; 3]> = bodies
+            .ids()
+            .map(|id| {
+                let artistic = Size {
+                    semi_major_axis: bodies[id].orbit.semi_major_axis,
+                    radius: bodies[id].radius,
+                };
+                (id, [artistic; 3])
+            })
+            .collect();
+
+        // Radios primero: las lunas se ubican en radios de su planeta
+        let mut size_factors = [1.0; 3];
+        for mode in modes {
+            let ratios: Vec<f32> = bodies
+                .ids()
+                .filter_map(|id| Some((radius_in(mode, bodies[id].true_radius?) / bodies[id].radius).ln()))
+                .collect();
+            if !ratios.is_empty() {
+                size_factors[mode.index()] = (ratios.iter().sum::<f32>() / ratios.len() as f32).exp();
+            }
+            for id in bodies.ids() {
+                let radius = bodies[id]
+                    .true_radius
+                    .map_or(bodies[id].radius * size_factors[mode.index()], |km| radius_in(mode, km));
+                sizes.get_mut(&id).expect("Id del registro")[mode.index()].radius = radius;
+            }
+        }
+
+        // Los cometas quedan fuera de la curva: con órbitas tan excéntricas
+        // su semieje artístico no sigue el orden de los planetas
+        let mut curves: [Vec<(f32, f32)>; 3] = Default::default();
+        for mode in modes {
+            let mut curve: Vec<(f32, f32)> = bodies
+                .planets()
+                .into_iter()
+                .filter_map(|id| Some((bodies[id].orbit.semi_major_axis, distance_in(mode, bodies[id].true_semi_major_axis?))))
+                .collect();
+            curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+            curves[mode.index()] = curve;
+        }
+
+        for &id in bodies.update_order() {
+            let body = &bodies[id];
+            if body.is_sun {
+                continue;
+            }
+            for mode in modes {
+                let semi_major_axis = match (body.parent.filter(|&p| !bodies[p].is_sun), body.true_semi_major_axis) {
+                    (None, Some(au)) => distance_in(mode, au),
+                    (None, None) => remap(&curves[mode.index()], body.orbit.semi_major_axis),
+                    (Some(_), Some(au)) if mode == ScaleMode::True => au * UNITS_PER_AU,
+                    (Some(p), au) => {
+                        let parent_radius = sizes[&p][mode.index()].radius;
+                        match (au, bodies[p].true_radius) {
+                            // Logarítmica: la distancia en radios del planeta también
+                            // se comprime
+                            (Some(au), Some(km)) => parent_radius * (1.0 + (1.0 + au * KM_PER_AU / km).log10()),
+                            _ => body.orbit.semi_major_axis * parent_radius / bodies[p].radius,
+                        }
+                    }
+                };
+                sizes.get_mut(&id).expect("Id del registro")[mode.index()].semi_major_axis = semi_major_axis;
+            }
+        }
+
+        let bodies = bodies
+            .ids()
+            .map(|id| BodyScale {
+                id,
+                sizes: sizes[&id],
+                ring: bodies[id].ring.as_ref().map(|r| (r.inner_radius, r.outer_radius)),
+            })
+            .collect();
+        Self {
+            mode: ScaleMode::Artistic,
+            from: ScaleMode::Artistic,
+            progress: 1.0,
+            bodies,
+            curves,
+            size_factors,
+        }
+    }
+
+    /// Empieza la transición hacia otra escala. Durante una transición no se
+    /// puede pedir otra: partiría de la escala de destino y los cuerpos
+    /// saltarían a ella.
+    pub fn set_mode(&mut self, mode: ScaleMode) {
+        if mode == self.mode || self.is_animating() {
+            return;
+        }
+        self.from = self.mode;
+        self.mode = mode;
+        self.progress = 0.0;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.progress < 1.0
+    }
+
+    pub fn label(&self) -> String {
+        if self.is_animating() {
+            format!("{} -> {}", self.from.label(), self.mode.label())
+        } else {
+            self.mode.label().to_string()
+        }
+    }
+
+    /// Avanza la transición y aplica los tamaños a cuerpos, anillos y
+    /// cinturones. Devuelve verdadero si algo cambió de tamaño en este frame.
+    pub fn update(&mut self, delta_time: f32, bodies: &mut BodyRegistry, belts: &mut [Belt]) -> bool {
+        if !self.is_animating() {
+            return false;
+        }
+        self.progress = (self.progress + delta_time / TRANSITION_SECONDS).min(1.0);
+        self.apply(bodies, belts);
+        true
+    }
+
+    /// Vuelve a aplicar los tamaños actuales (después de restaurar órbitas,
+    /// por ejemplo al salir de las efemérides)
+    pub fn apply(&self, bodies: &mut BodyRegistry, belts: &mut [Belt]) {
+        let t = self.blend();
+        for scale in &self.bodies {
+            let (from, to) = (scale.sizes[self.from.index()], scale.sizes[self.mode.index()]);
+            let body = &mut bodies[scale.id];
+            body.radius = geometric(from.radius, to.radius, t);
+            if !body.is_sun {
+                body.orbit.semi_major_axis = geometric(from.semi_major_axis, to.semi_major_axis, t);
+            }
+            if let (Some(ring), Some((inner, outer))) = (body.ring.as_mut(), scale.ring) {
+                let factor = body.radius / scale.sizes[0].radius;
+                ring.inner_radius = inner * factor;
+                ring.outer_radius = outer * factor;
+            }
+        }
+
+        let size = self.size_factor();
+        for belt in belts.iter_mut() {
+            belt.rescale(|a| self.distance_at(a), size);
+        }
+    }
+
+    /// Avance suavizado de la transición (ease-in-out)
+    fn blend(&self) -> f32 {
+        let t = self.progress;
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Radio actual / radio artístico de un cuerpo; escala los desvíos de
+    /// warp y las distancias de llegada
+    pub fn radius_factor(&self, bodies: &BodyRegistry, id: BodyId) -> f32 {
+        self.bodies
+            .iter()
+            .find(|s| s.id == id)
+            .map_or(1.0, |s| bodies[id].radius / s.sizes[0].radius)
+    }
+
+    /// Factor de tamaño promedio de la escala actual; escala el margen de
+    /// colisión
+    pub fn size_factor(&self) -> f32 {
+        geometric(self.size_factors[self.from.index()], self.size_factors[self.mode.index()], self.blend())
+    }
+
+    /// Posición actual de una distancia artística a la estrella principal;
+    /// reubica cinturones y cometas
+    pub fn distance_at(&self, artistic: f32) -> f32 {
+        let from = remap(&self.curves[self.from.index()], artistic);
+        let to = remap(&self.curves[self.mode.index()], artistic);
+        geometric(from, to, self.blend())
+    }
+
+    /// Distancia actual / distancia artística a una distancia dada de la
+    /// estrella; escala la velocidad de la cámara y las poses fijas
+    pub fn distance_factor_at(&self, artistic: f32) -> f32 {
+        self.distance_at(artistic) / artistic
+    }
+}
+
+fn distance_in(mode: ScaleMode, au: f32) -> f32 {
+    match mode {
+        ScaleMode::Artistic => au,
+        ScaleMode::Logarithmic => LOG_DISTANCE * (1.0 + au / LOG_DISTANCE_KNEE).log10(),
+        ScaleMode::True => au * UNITS_PER_AU,
+    }
+}
+
+fn radius_in(mode: ScaleMode, km: f32) -> f32 {
+    match mode {
+        ScaleMode::Artistic => km,
+        ScaleMode::Logarithmic => LOG_RADIUS * (1.0 + km / LOG_RADIUS_KNEE).log10(),
+        ScaleMode::True => km / KM_PER_AU * UNITS_PER_AU,
+    }
+}
+
+/// Interpolación lineal a tramos por los puntos de la curva; fuera de ella
+/// se extiende de forma proporcional. Sin puntos (la escala artística) no
+/// cambia nada.
+fn remap(curve: &[(f32, f32)], artistic: f32) -> f32 {
+    let (Some(&first), Some(&last)) = (curve.first(), curve.last()) else {
+        return artistic;
+    };
+    if artistic <= first.0 {
+        return artistic * first.1 / first.0;
+    }
+    if artistic >= last.0 {
+        return artistic * last.1 / last.0;
+    }
+    let k = curve.partition_point(|&(a, _)| a <= artistic).min(curve.len() - 1);
+    let ((a0, d0), (a1, d1)) = (curve[k - 1], curve[k]);
+    if a1 - a0 < 1e-6 {
+        return d0;
+    }
+    d0 + (d1 - d0) * (artistic - a0) / (a1 - a0)
+}
+
+/// Interpolación en escala logarítmica: los valores van de 0.04 a 2.2, o
+/// de 30 a 1000, y así cada orden de magnitud dura lo mismo
+fn geometric(from: f32, to: f32, t: f32) -> f32 {
+    if from <= 0.0 || to <= 0.0 {
+        return from + (to - from) * t;
+    }
+    from * (to / from).powf(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+    use raylib::prelude::Color;
+
+    const CURVE: [(f32, f32); 3] = [(10.0, 100.0), (20.0, 400.0), (40.0, 500.0)];
+
+    #[test]
+    fn remap_pasa_por_los_puntos() {
+        for (artistic, scaled) in CURVE {
+            assert!((remap(&CURVE, artistic) - scaled).abs() < 1e-4);
+        }
+        // Lineal entre puntos
+        assert!((remap(&CURVE, 15.0) - 250.0).abs() < 1e-4);
+        assert!((remap(&CURVE, 30.0) - 450.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn remap_se_extiende_proporcional() {
+        assert!((remap(&CURVE, 5.0) - 50.0).abs() < 1e-4);
+        assert!((remap(&CURVE, 80.0) - 1000.0).abs() < 1e-3);
+        assert_eq!(remap(&CURVE, 0.0), 0.0);
+    }
+
+    #[test]
+    fn remap_sin_curva_no_cambia() {
+        assert_eq!(remap(&[], 12.5), 12.5);
+    }
+
+    #[test]
+    fn remap_con_semiejes_repetidos() {
+        let curve = [(10.0, 100.0), (10.0, 120.0), (20.0, 200.0)];
+        let value = remap(&curve, 10.0);
+        assert!(value.is_finite() && (100.0..=120.0).contains(&value));
+        assert!((remap(&curve, 15.0) - 160.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn geometric_en_los_extremos_y_al_medio() {
+        assert!((geometric(0.04, 2.2, 0.0) - 0.04).abs() < 1e-6);
+        assert!((geometric(0.04, 2.2, 1.0) - 2.2).abs() < 1e-5);
+        // A mitad de camino, la media geométrica
+        assert!((geometric(30.0, 1000.0, 0.5) - (30.0f32 * 1000.0).sqrt()).abs() < 1e-3);
+        assert!((geometric(1000.0, 30.0, 0.5) - (30.0f32 * 1000.0).sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn no_cambia_de_escala_a_mitad_de_transicion() {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(CelestialBody::new_sun("Sol", 8.0, Color::WHITE));
+        let mut scales = ScaleSystem::new(&bodies);
+        scales.set_mode(ScaleMode::True);
+        scales.update(TRANSITION_SECONDS * 0.5, &mut bodies, &mut []);
+        scales.set_mode(ScaleMode::Logarithmic);
+        assert_eq!(scales.label(), "artistica -> real");
+        scales.update(TRANSITION_SECONDS, &mut bodies, &mut []);
+        assert!(!scales.is_animating());
+        scales.set_mode(ScaleMode::Logarithmic);
+        assert_eq!(scales.label(), "real -> logaritmica");
+    }
+
+    #[test]
+    fn geometric_sin_logaritmo_con_ceros() {
+        assert!((geometric(0.0, 10.0, 0.25) - 2.5).abs() < 1e-6);
+        assert!((geometric(4.0, 0.0, 0.5) - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,568 @@
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+use crate::belt::{Belt, BeltSettings};
+use crate::body_registry::BodyRegistry;
+use crate::celestial_body::CelestialBody;
+use crate::floating_origin::DVec3;
+use crate::orbital_elements::{OrbitalElements, MAX_ECCENTRICITY};
+use crate::physical::PhysicalData;
+use crate::rings::{RingBand, RingSystem};
+use crate::shader::{BodyType, NAMED_SHADERS};
+
+/// Escenario que se carga si no se indica otro con `--escenario`
+pub const DEFAULT_SCENARIO: &str = "assets/scenarios/sistema_solar.ron";
+
+/// Definición de un sistema tal como se escribe en el archivo RON
+#[derive(Debug, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub bodies: Vec<BodyDef>,
+    /// Cinturones de cuerpos menores alrededor de la estrella principal
+    #[serde(default)]
+    pub belts: Vec<BeltSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BodyDef {
+    pub name: String,
+    pub body_type: BodyType,
+    /// Nombre del cuerpo alrededor del cual orbita; sin padre orbita el
+    /// origen, donde está la estrella principal
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Posición fija de una estrella (por defecto el origen)
+    #[serde(default)]
+    pub position: Option<(f64, f64, f64)>,
+    pub radius: f32,
+    /// Radio real en km, para las escalas real y logarítmica
+    #[serde(default)]
+    pub true_radius: Option<f32>,
+    /// (r, g, b, a)
+    pub color: (u8, u8, u8, u8),
+    /// Masa en masas solares
+    #[serde(default)]
+    pub mass: f32,
+    /// Las estrellas no llevan órbita; el resto de cuerpos sí
+    #[serde(default)]
+    pub orbit: Option<OrbitDef>,
+    /// Color de la línea de órbita (por defecto gris, más tenue en las lunas)
+    #[serde(default)]
+    pub orbit_color: Option<(u8, u8, u8, u8)>,
+    /// Grados por segundo de simulación
+    #[serde(default)]
+    pub orbital_speed: f32,
+    #[serde(default = "default_rotation_speed")]
+    pub rotation_speed: f32,
+    /// Oblicuidad en grados; más de 90° gira en sentido retrógrado
+    #[serde(default)]
+    pub axial_tilt: f32,
+    #[serde(default)]
+    pub shader: Option<String>,
+    #[serde(default)]
+    pub ring: Option<RingDef>,
+    /// Registrar la estela del cuerpo (activada por defecto)
+    #[serde(default = "default_trail")]
+    pub trail: bool,
+    /// Día sidéreo en horas
+    #[serde(default)]
+    pub sidereal_day: Option<f32>,
+    /// Albedo de Bond, entre 0 y 1
+    #[serde(default)]
+    pub albedo: Option<f32>,
+    /// Temperatura media en K; sin ella se usa la de equilibrio
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// (gas, % en volumen)
+    #[serde(default)]
+    pub atmosphere: Vec<(String, f32)>,
+}
+
+/// Elementos keplerianos en grados; solo el semieje mayor es obligatorio
+#[derive(Debug, Deserialize)]
+pub struct OrbitDef {
+    pub semi_major_axis: f32,
+    /// Semieje mayor real en UA, para las escalas real y logarítmica
+    #[serde(default)]
+    pub true_semi_major_axis: Option<f32>,
+    #[serde(default)]
+    pub eccentricity: f32,
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub ascending_node: f32,
+    #[serde(default)]
+    pub arg_periapsis: f32,
+    #[serde(default)]
+    pub mean_anomaly: f32,
+    /// Medir los elementos sobre el ecuador del padre
+    #[serde(default)]
+    pub equatorial: bool,
+}
+
+/// Anillos de un cuerpo. El perfil radial sale de `bands` (procedural) o
+/// de la fila central de la imagen `texture`; se usa uno de los dos.
+#[derive(Debug, Deserialize)]
+pub struct RingDef {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    #[serde(default)]
+    pub bands: Vec<BandDef>,
+    #[serde(default)]
+    pub texture: Option<String>,
+    /// Divisiones (interior, exterior) que quedan transparentes
+    #[serde(default)]
+    pub gaps: Vec<(f32, f32)>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BandDef {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub color: (u8, u8, u8, u8),
+}
+
+fn default_rotation_speed() -> f32 {
+    1.0
+}
+
+fn default_trail() -> bool {
+    true
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(String, std::io::Error),
+    Parse(String, ron::error::SpannedError),
+    Invalid(String, Vec<String>),
+    /// Opción de línea de comandos sin la ruta
+    MissingPath(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScenarioError::Io(path, err) => write!(f, "no se pudo leer '{}': {}", path, err),
+            ScenarioError::Parse(path, err) => write!(f, "'{}' no es un escenario válido: {}", path, err),
+            ScenarioError::Invalid(path, problems) => {
+                write!(f, "'{}' tiene {} error(es):", path, problems.len())?;
+                for problem in problems {
+                    write!(f, "\n  - {}", problem)?;
+                }
+                Ok(())
+            }
+            ScenarioError::MissingPath(flag) => write!(f, "falta la ruta del escenario después de '{}'", flag),
+        }
+    }
+}
+
+impl Scenario {
+    /// Lee y valida un escenario. Todos los problemas de validación se
+    /// reportan juntos para poder corregir el archivo de una vez.
+    pub fn load(path: &str) -> Result<Self, ScenarioError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| ScenarioError::Io(path.to_string(), err))?;
+        let scenario: Scenario = ron::from_str(&text)
+            .map_err(|err| ScenarioError::Parse(path.to_string(), err))?;
+
+        let problems = scenario.validate();
+        if problems.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(ScenarioError::Invalid(path.to_string(), problems))
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if !self.bodies.iter().any(|b| b.body_type == BodyType::Star) {
+            problems.push("el escenario necesita al menos una estrella (body_type: Star)".to_string());
+        }
+
+        for (i, body) in self.bodies.iter().enumerate() {
+            let name = &body.name;
+            if name.trim().is_empty() {
+                problems.push(format!("el cuerpo #{} no tiene nombre", i + 1));
+            }
+            if self.bodies[..i].iter().any(|b| &b.name == name) {
+                problems.push(format!("'{}': nombre repetido", name));
+            }
+            if !body.radius.is_finite() || body.radius <= 0.0 {
+                problems.push(format!("'{}': el radio debe ser mayor que 0 (es {})", name, body.radius));
+            }
+            if body.true_radius.is_some_and(|r| !r.is_finite() || r <= 0.0) {
+                problems.push(format!("'{}': el radio real debe ser mayor que 0", name));
+            }
+            if !body.mass.is_finite() || body.mass < 0.0 {
+                problems.push(format!("'{}': la masa no puede ser negativa (es {})", name, body.mass));
+            }
+            if body.sidereal_day.is_some_and(|h| !h.is_finite() || h <= 0.0) {
+                problems.push(format!("'{}': el día sidéreo debe ser mayor que 0", name));
+            }
+            if body.albedo.is_some_and(|a| !(0.0..=1.0).contains(&a)) {
+                problems.push(format!("'{}': el albedo debe estar entre 0 y 1", name));
+            }
+            if body.temperature.is_some_and(|t| !t.is_finite() || t <= 0.0) {
+                problems.push(format!("'{}': la temperatura debe ser mayor que 0 K", name));
+            }
+            if body.atmosphere.iter().any(|(_, percent)| !(*percent > 0.0 && *percent <= 100.0)) {
+                problems.push(format!("'{}': cada gas de la atmósfera debe estar entre 0 y 100 %", name));
+            }
+            let total: f32 = body.atmosphere.iter().map(|(_, percent)| percent).sum();
+            if total > 100.5 {
+                problems.push(format!("'{}': la atmósfera suma {}% (más de 100)", name, total));
+            }
+            if !(0.0..=180.0).contains(&body.axial_tilt) {
+                problems.push(format!("'{}': la oblicuidad debe estar entre 0 y 180 (es {})", name, body.axial_tilt));
+            }
+            // Un NaN o un infinito llegaría a las posiciones sin que nada lo
+            // detenga
+            let mut angles = vec![("orbital_speed", body.orbital_speed), ("rotation_speed", body.rotation_speed)];
+            if let Some(orbit) = &body.orbit {
+                angles.extend([
+                    ("inclination", orbit.inclination),
+                    ("ascending_node", orbit.ascending_node),
+                    ("arg_periapsis", orbit.arg_periapsis),
+                    ("mean_anomaly", orbit.mean_anomaly),
+                ]);
+            }
+            for (field, value) in angles.into_iter().filter(|(_, value)| !value.is_finite()) {
+                problems.push(format!("'{}': {} debe ser un número finito (es {})", name, field, value));
+            }
+            if body.position.is_some_and(|(x, y, z)| !(x.is_finite() && y.is_finite() && z.is_finite())) {
+                problems.push(format!("'{}': la posición debe ser finita", name));
+            }
+
+            if body.body_type == BodyType::Star {
+                if body.orbit.is_some() || body.parent.is_some() {
+                    problems.push(format!("'{}': una estrella no puede tener órbita ni padre", name));
+                }
+            } else {
+                if body.position.is_some() {
+                    problems.push(format!("'{}': solo las estrellas tienen posición fija", name));
+                }
+                match &body.orbit {
+                    None => problems.push(format!("'{}': falta la órbita", name)),
+                    Some(orbit) => {
+                        if !orbit.semi_major_axis.is_finite() || orbit.semi_major_axis <= 0.0 {
+                            problems.push(format!(
+                                "'{}': el semieje mayor debe ser mayor que 0 (es {})",
+                                name, orbit.semi_major_axis
+                            ));
+                        }
+                        if !(0.0..=MAX_ECCENTRICITY).contains(&orbit.eccentricity) {
+                            problems.push(format!(
+                                "'{}': la excentricidad debe estar entre 0 y {} (es {})",
+                                name, MAX_ECCENTRICITY, orbit.eccentricity
+                            ));
+                        }
+                        if orbit.true_semi_major_axis.is_some_and(|a| !a.is_finite() || a <= 0.0) {
+                            problems.push(format!("'{}': el semieje mayor real debe ser mayor que 0", name));
+                        }
+                        if orbit.equatorial && body.parent.is_none() {
+                            problems.push(format!("'{}': una órbita ecuatorial necesita padre", name));
+                        }
+                    }
+                }
+            }
+
+            if let Some(parent) = &body.parent {
+                if parent == name {
+                    problems.push(format!("'{}': un cuerpo no puede orbitarse a sí mismo", name));
+                } else if !self.bodies.iter().any(|b| &b.name == parent) {
+                    problems.push(format!("'{}': el padre '{}' no existe", name, parent));
+                } else if self.in_parent_cycle(i) {
+                    problems.push(format!("'{}': la cadena de padres forma un ciclo", name));
+                }
+            }
+
+            let unknown_shader = body.shader.as_ref().filter(|s| !NAMED_SHADERS.contains(&s.as_str()));
+            if let Some(shader) = unknown_shader {
+                problems.push(format!(
+                    "'{}': shader desconocido '{}' (opciones: {})",
+                    name,
+                    shader,
+                    NAMED_SHADERS.join(", ")
+                ));
+            }
+
+            if let Some(ring) = &body.ring {
+                problems.extend(validate_ring(name, ring));
+            }
+        }
+
+        for belt in &self.belts {
+            let name = &belt.name;
+            let (a_min, a_max) = belt.semi_major_axis;
+            let (r_min, r_max) = belt.radius;
+            if belt.count == 0 {
+                problems.push(format!("cinturón '{}': count debe ser mayor que 0", name));
+            }
+            if !(a_min > 0.0 && a_min <= a_max && a_max.is_finite()) {
+                problems.push(format!(
+                    "cinturón '{}': semieje mayor inválido ({} - {}); se requiere 0 < mínimo <= máximo",
+                    name, a_min, a_max
+                ));
+            }
+            if !(r_min > 0.0 && r_min <= r_max && r_max.is_finite()) {
+                problems.push(format!(
+                    "cinturón '{}': radio inválido ({} - {}); se requiere 0 < mínimo <= máximo",
+                    name, r_min, r_max
+                ));
+            }
+            let sigmas = [belt.eccentricity_sigma, belt.inclination_sigma];
+            if !sigmas.iter().all(|sigma| sigma.is_finite() && *sigma >= 0.0) {
+                problems.push(format!("cinturón '{}': las dispersiones deben ser finitas y no negativas", name));
+            }
+        }
+
+        problems
+    }
+
+    /// Sigue la cadena de padres por nombre; si vuelve al cuerpo inicial hay
+    /// un ciclo. Más pasos que cuerpos también implica un ciclo.
+    fn in_parent_cycle(&self, start: usize) -> bool {
+        let mut current = start;
+        for _ in 0..self.bodies.len() {
+            let Some(parent) = &self.bodies[current].parent else {
+                return false;
+            };
+            match self.bodies.iter().position(|b| &b.name == parent) {
+                Some(p) if p == start => return true,
+                Some(p) => current = p,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Verdadero si el padre del cuerpo no es una estrella
+    fn orbits_moon(&self, def: &BodyDef) -> bool {
+        let parent = def.parent.as_ref().and_then(|p| self.bodies.iter().find(|b| &b.name == p));
+        parent.is_some_and(|p| p.body_type != BodyType::Star)
+    }
+
+    /// Construye el registro de cuerpos: primero se insertan todos y luego
+    /// se enlazan los padres por nombre, así que el orden del archivo no importa.
+    pub fn build_registry(&self) -> BodyRegistry {
+        let mut registry = BodyRegistry::new();
+
+        for def in &self.bodies {
+            let (r, g, b, a) = def.color;
+            let color = Color::new(r, g, b, a);
+
+            let mut body = match &def.orbit {
+                None => CelestialBody::new_sun(&def.name, def.radius, color),
+                Some(orbit) => {
+                    let elements = OrbitalElements::new(
+                        orbit.semi_major_axis,
+                        orbit.eccentricity,
+                        orbit.inclination,
+                        orbit.ascending_node,
+                        orbit.arg_periapsis,
+                        orbit.mean_anomaly,
+                    );
+                    CelestialBody::new_planet(
+                        &def.name, def.radius, color, elements, def.orbital_speed, def.rotation_speed,
+                    )
+                }
+            }
+            .with_mass(def.mass);
+
+            body.body_type = def.body_type;
+            body.is_sun = def.body_type == BodyType::Star;
+            body.rotation_speed = def.rotation_speed;
+            body.axial_tilt = def.axial_tilt;
+            body.equatorial_orbit = def.orbit.as_ref().is_some_and(|o| o.equatorial);
+            if let Some((x, y, z)) = def.position {
+                body.position = DVec3::new(x, y, z);
+            }
+            if let Some(shader) = &def.shader {
+                body.shader = shader.clone();
+            }
+            body.ring = def.ring.as_ref().map(build_ring);
+            body.trail = def.trail;
+            body.true_radius = def.true_radius;
+            body.true_semi_major_axis = def.orbit.as_ref().and_then(|o| o.true_semi_major_axis);
+            body.physical = PhysicalData {
+                sidereal_day: def.sidereal_day,
+                albedo: def.albedo,
+                temperature: def.temperature,
+                atmosphere: def.atmosphere.clone(),
+            };
+            body.orbit_color = match def.orbit_color {
+                Some((r, g, b, a)) => Color::new(r, g, b, a),
+                None if self.orbits_moon(def) => Color::new(80, 80, 100, 90),
+                None => body.orbit_color,
+            };
+
+            registry.insert(body);
+        }
+
+        for def in &self.bodies {
+            let Some(parent) = &def.parent else { continue };
+            let child = registry.find(&def.name).expect("Cuerpo recién insertado");
+            let parent = registry.find(parent).expect("Padre validado");
+            registry
+                .set_parent(child, Some(parent))
+                .expect("Los ciclos se rechazan al validar");
+        }
+
+        // Posiciones iniciales sobre los rieles, ya con los padres enlazados
+        registry.update_rails(0.0);
+        registry
+    }
+
+    /// Genera los cinturones alrededor de la estrella principal
+    pub fn build_belts(&self, registry: &BodyRegistry) -> Vec<Belt> {
+        let primary_mass = registry.primary().map_or(1.0, |id| registry[id].mass);
+        self.belts
+            .iter()
+            .map(|settings| Belt::generate(settings, primary_mass))
+            .collect()
+    }
+}
+
+fn validate_ring(name: &str, ring: &RingDef) -> Vec<String> {
+    let mut problems = Vec::new();
+    let (inner, outer) = (ring.inner_radius, ring.outer_radius);
+    if !(inner > 0.0 && inner < outer && outer.is_finite()) {
+        problems.push(format!(
+            "'{}': anillo inválido ({} - {}); se requiere 0 < interior < exterior",
+            name, inner, outer
+        ));
+    }
+
+    let ranges = ring
+        .bands
+        .iter()
+        .map(|band| ("banda", band.inner_radius, band.outer_radius))
+        .chain(ring.gaps.iter().map(|&(from, to)| ("división", from, to)));
+    for (kind, from, to) in ranges {
+        if !(inner <= from && from < to && to <= outer) {
+            problems.push(format!(
+                "'{}': {} de anillo inválida ({} - {}); debe quedar dentro de {} - {}",
+                name, kind, from, to, inner, outer
+            ));
+        }
+    }
+
+    match (&ring.texture, ring.bands.is_empty()) {
+        (None, true) => problems.push(format!("'{}': el anillo necesita `bands` o `texture`", name)),
+        (Some(_), false) => problems.push(format!("'{}': el anillo usa `bands` o `texture`, no ambos", name)),
+        (Some(path), true) if Image::load_image(path).is_err() => {
+            problems.push(format!("'{}': no se pudo cargar la textura de anillo '{}'", name, path));
+        }
+        _ => {}
+    }
+    problems
+}
+
+fn build_ring(ring: &RingDef) -> RingSystem {
+    match &ring.texture {
+        Some(path) => {
+            let mut image = Image::load_image(path).expect("Textura de anillo validada");
+            RingSystem::from_image(ring.inner_radius, ring.outer_radius, &mut image, &ring.gaps)
+        }
+        None => {
+            let bands: Vec<RingBand> = ring
+                .bands
+                .iter()
+                .map(|band| {
+                    let (r, g, b, a) = band.color;
+                    RingBand {
+                        inner_radius: band.inner_radius,
+                        outer_radius: band.outer_radius,
+                        color: Color::new(r, g, b, a),
+                    }
+                })
+                .collect();
+            RingSystem::from_bands(ring.inner_radius, ring.outer_radius, &bands, &ring.gaps)
+        }
+    }
+}
+
+/// Ruta del escenario pasada por línea de comandos:
+/// `--escenario <ruta>`, `--scenario <ruta>` o la forma `--escenario=<ruta>`.
+/// Sin la opción se usa el escenario por defecto; con la opción y sin ruta
+/// es un error, para no cargar otro escenario sin avisar.
+pub fn path_from_args() -> Result<String, ScenarioError> {
+    path_from(std::env::args().skip(1))
+}
+
+fn path_from(mut args: impl Iterator<Item = String>) -> Result<String, ScenarioError> {
+    while let Some(arg) = args.next() {
+        for flag in ["--escenario", "--scenario"] {
+            let path = if arg == flag {
+                args.next().filter(|path| !path.starts_with("--"))
+            } else if let Some(path) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+                Some(path.to_string())
+            } else {
+                continue;
+            };
+            return path.filter(|path| !path.is_empty()).ok_or(ScenarioError::MissingPath(flag.to_string()));
+        }
+    }
+    Ok(DEFAULT_SCENARIO.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn ruta_por_linea_de_comandos() {
+        assert_eq!(path_from(args(&[])).unwrap(), DEFAULT_SCENARIO);
+        assert_eq!(path_from(args(&["--escenario", "a.ron"])).unwrap(), "a.ron");
+        assert_eq!(path_from(args(&["--scenario=b.ron"])).unwrap(), "b.ron");
+        assert_eq!(path_from(args(&["--otra", "--escenario=c.ron"])).unwrap(), "c.ron");
+    }
+
+    #[test]
+    fn el_escenario_por_defecto_es_valido() {
+        let scenario = Scenario::load(DEFAULT_SCENARIO).unwrap_or_else(|err| panic!("{}", err));
+        assert!(scenario.bodies.iter().any(|b| b.name == "Tierra"));
+        assert!(!scenario.belts.is_empty());
+    }
+
+    #[test]
+    fn reporta_todos_los_errores() {
+        let text = r#"(
+            name: "Roto",
+            bodies: [
+                (name: "A", body_type: RockyPlanet, radius: NaN, color: (0, 0, 0, 255),
+                 orbit: Some((semi_major_axis: 10.0, eccentricity: 0.995, inclination: inf))),
+                (name: "A", body_type: Moon, parent: Some("B"), radius: 1.0, color: (0, 0, 0, 255),
+                 orbital_speed: NaN, orbit: Some((semi_major_axis: 1.0))),
+            ],
+        )"#;
+        let scenario: Scenario = ron::from_str(text).unwrap();
+        let problems = scenario.validate();
+        let expected = [
+            "el escenario necesita al menos una estrella",
+            "'A': el radio debe ser mayor que 0",
+            "'A': la excentricidad debe estar entre 0 y 0.99",
+            "'A': inclination debe ser un número finito",
+            "'A': nombre repetido",
+            "'A': orbital_speed debe ser un número finito",
+            "'A': el padre 'B' no existe",
+        ];
+        for message in expected {
+            assert!(problems.iter().any(|p| p.starts_with(message)), "falta '{}' en {:?}", message, problems);
+        }
+        assert_eq!(problems.len(), expected.len(), "{:?}", problems);
+    }
+
+    #[test]
+    fn opcion_sin_ruta_es_un_error() {
+        for list in [&["--escenario"][..], &["--scenario="], &["--escenario", "--otra"]] {
+            assert!(matches!(path_from(args(list)), Err(ScenarioError::MissingPath(_))));
+        }
+    }
+}
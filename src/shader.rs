@@ -1,12 +1,14 @@
 use raylib::prelude::*;
 use std::collections::HashMap;
+use serde::Deserialize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum BodyType {
     Star,
     RockyPlanet,
     GasGiant,
     Moon,
+    Comet,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -338,6 +340,11 @@ fn moon_shader(uv: Vec2, moon_color: Vec3, _time: f32) -> Vec3 {
     color
 }
 
+/// Shaders con nombre propio; cualquier otro nombre usa el del tipo de cuerpo
+pub const NAMED_SHADERS: [&str; 9] = [
+    "Sol", "Mercurio", "Venus", "Tierra", "Marte", "Jupiter", "Saturno", "Urano", "Neptuno",
+];
+
 // ===== SHADER MANAGER =====
 pub struct ShaderManager { textures: HashMap<BodyType, Image> }
 
@@ -370,6 +377,8 @@ impl ShaderManager {
                         BodyType::Moon => moon_shader(uv, cv, time),
                         BodyType::GasGiant => saturno_shader(uv, time),
                         BodyType::RockyPlanet => moon_shader(uv, cv, time),
+                        // Núcleo oscuro: hielo sucio cubierto de polvo
+                        BodyType::Comet => moon_shader(uv, cv * 0.5, time),
                     }
                 };
                 img.draw_pixel(x, y, pixel.to_color());
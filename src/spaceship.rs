@@ -1,5 +1,7 @@
 use raylib::prelude::*;
 
+use crate::floating_origin::DVec3;
+
 pub struct Spaceship {
     pub offset_from_camera: Vector3,
     pub scale: f32,
@@ -29,7 +31,7 @@ impl Spaceship {
         }
     }
 
-    pub fn get_position(&self, camera_pos: Vector3, camera_target: Vector3) -> Vector3 {
+    pub fn get_position(&self, camera_pos: DVec3, camera_target: DVec3) -> DVec3 {
         if self.orbit_demo {
             let a = self.orbit_angle;
             let y = self.orbit_radius * a.sin();
@@ -37,16 +39,17 @@ impl Spaceship {
             let t = self.tilt_deg.to_radians();
             let y2 = y * t.cos() - z * t.sin();
             let z2 = y * t.sin() + z * t.cos();
-            return Vector3::new(0.0, y2, z2);
+            return DVec3::from(Vector3::new(0.0, y2, z2));
         }
-        let forward = (camera_target - camera_pos).normalized();
+        let forward = (camera_target - camera_pos).to_f32().normalized();
         let right = forward.cross(Vector3::new(0.0, 1.0, 0.0)).normalized();
         let up = right.cross(forward);
         camera_pos + forward * self.offset_from_camera.z + right * self.offset_from_camera.x + up * self.offset_from_camera.y
     }
 
-    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, model: &Model, camera_pos: Vector3, camera_target: Vector3) {
-        let position = self.get_position(camera_pos, camera_target);
+    /// Se dibuja relativa a la cámara, como el resto de la escena
+    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, model: &Model, camera_pos: DVec3, camera_target: DVec3) {
+        let position = self.get_position(camera_pos, camera_target).relative_to(camera_pos);
         let forward = if self.orbit_demo {
             let a = self.orbit_angle;
             let dy = self.orbit_radius * a.cos();
@@ -56,7 +59,7 @@ impl Spaceship {
             let dz2 = dy * t.sin() + dz * t.cos();
             Vector3::new(0.0, dy2, dz2).normalized()
         } else {
-            (camera_target - camera_pos).normalized()
+            (camera_target - camera_pos).to_f32().normalized()
         };
         let yaw = forward.z.atan2(forward.x);
         let yaw_degrees = yaw.to_degrees();
@@ -74,8 +77,7 @@ impl Spaceship {
 
     pub fn update(&mut self, delta_time: f32) {
         if self.orbit_demo {
-            self.orbit_angle += self.orbit_speed * delta_time;
-            if self.orbit_angle > std::f32::consts::TAU { self.orbit_angle -= std::f32::consts::TAU; }
+            self.orbit_angle = (self.orbit_angle + self.orbit_speed * delta_time).rem_euclid(std::f32::consts::TAU);
         }
     }
 
@@ -0,0 +1,154 @@
+use raylib::prelude::*;
+use std::collections::VecDeque;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::floating_origin::DVec3;
+
+/// Muestras por estela; con un intervalo de 1/20 s cubre 30 s de simulación
+/// (un cuarto de año terrestre) a velocidad normal
+const TRAIL_CAPACITY: usize = 600;
+/// Segundos de simulación entre muestras
+const SAMPLE_INTERVAL: f32 = 0.05;
+
+/// Marco en el que se dibujan las estelas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailFrame {
+    /// Relativo a la estrella del cuerpo: la Luna dibuja una curva ondulada
+    /// alrededor de la órbita terrestre
+    Heliocentric,
+    /// Relativo al padre: la Luna dibuja su órbita alrededor de la Tierra
+    ParentRelative,
+}
+
+impl TrailFrame {
+    pub fn label(self) -> &'static str {
+        match self {
+            TrailFrame::Heliocentric => "heliocentrico",
+            TrailFrame::ParentRelative => "relativo al padre",
+        }
+    }
+}
+
+/// Posición guardada en ambos marcos, así cambiar de marco no borra la
+/// historia
+#[derive(Clone, Copy)]
+struct TrailSample {
+    from_star: DVec3,
+    from_parent: DVec3,
+}
+
+struct Trail {
+    body: BodyId,
+    samples: VecDeque<TrailSample>,
+}
+
+/// Estelas con la trayectoria realmente recorrida. A diferencia de las
+/// órbitas analíticas, muestran las perturbaciones del modo N-cuerpos.
+pub struct TrailSystem {
+    pub enabled: bool,
+    pub frame: TrailFrame,
+    trails: Vec<Trail>,
+    since_last_sample: f32,
+}
+
+impl TrailSystem {
+    /// Una estela por cada cuerpo con `trail` activo en el escenario
+    pub fn new(bodies: &BodyRegistry) -> Self {
+        let trails = bodies
+            .ids()
+            .filter(|&id| bodies[id].trail && !bodies[id].is_sun)
+            .map(|body| Trail {
+                body,
+                samples: VecDeque::with_capacity(TRAIL_CAPACITY),
+            })
+            .collect();
+        Self {
+            enabled: false,
+            frame: TrailFrame::Heliocentric,
+            trails,
+            since_last_sample: 0.0,
+        }
+    }
+
+    pub fn toggle_frame(&mut self) {
+        self.frame = match self.frame {
+            TrailFrame::Heliocentric => TrailFrame::ParentRelative,
+            TrailFrame::ParentRelative => TrailFrame::Heliocentric,
+        };
+    }
+
+    /// Olvida la historia; se usa cuando los cuerpos saltan de posición
+    /// (efemérides, saltar a fecha)
+    pub fn clear(&mut self) {
+        for trail in &mut self.trails {
+            trail.samples.clear();
+        }
+    }
+
+    /// Registra una muestra cada `SAMPLE_INTERVAL` segundos de simulación,
+    /// también con el reloj en reversa. Se registra aunque no se dibuje, así
+    /// la estela ya existe al activarla.
+    pub fn record(&mut self, bodies: &BodyRegistry, sim_delta: f32) {
+        self.since_last_sample += sim_delta.abs();
+        if self.since_last_sample < SAMPLE_INTERVAL {
+            return;
+        }
+        self.since_last_sample = 0.0;
+
+        for trail in &mut self.trails {
+            let body = &bodies[trail.body];
+            if !body.active {
+                continue;
+            }
+            let star = bodies.star_of(trail.body).map_or(DVec3::zero(), |s| bodies[s].position);
+            let parent = body.parent.map_or(star, |p| bodies[p].position);
+
+            if trail.samples.len() == TRAIL_CAPACITY {
+                trail.samples.pop_front();
+            }
+            trail.samples.push_back(TrailSample {
+                from_star: body.position - star,
+                from_parent: body.position - parent,
+            });
+        }
+    }
+
+    /// Polilínea que se desvanece de la muestra más reciente a la más antigua,
+    /// relativa a la cámara (`origin`)
+    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, bodies: &BodyRegistry, origin: DVec3) {
+        if !self.enabled {
+            return;
+        }
+
+        for trail in &self.trails {
+            let body = &bodies[trail.body];
+            if !body.active || trail.samples.len() < 2 {
+                continue;
+            }
+            let star = bodies.star_of(trail.body).map_or(DVec3::zero(), |s| bodies[s].position);
+            let anchor = match self.frame {
+                TrailFrame::Heliocentric => star,
+                TrailFrame::ParentRelative => body.parent.map_or(star, |p| bodies[p].position),
+            };
+            let point = |sample: &TrailSample| {
+                let offset = match self.frame {
+                    TrailFrame::Heliocentric => sample.from_star,
+                    TrailFrame::ParentRelative => sample.from_parent,
+                };
+                (anchor + offset).relative_to(origin)
+            };
+
+            let count = trail.samples.len() as f32;
+            let mut previous = point(&trail.samples[0]);
+            for (i, sample) in trail.samples.iter().enumerate().skip(1) {
+                let current = point(sample);
+                let freshness = i as f32 / count;
+                let color = Color::new(body.color.r, body.color.g, body.color.b, (220.0 * freshness) as u8);
+                d.draw_line_3D(previous, current, color);
+                previous = current;
+            }
+            // Último tramo hasta la posición actual
+            d.draw_line_3D(previous, body.position.relative_to(origin), Color::new(body.color.r, body.color.g, body.color.b, 220));
+        }
+    }
+}
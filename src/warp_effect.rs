@@ -1,5 +1,7 @@
 use raylib::prelude::*;
 
+use crate::floating_origin::DVec3;
+
 pub struct WarpEffect {
     pub particle_count: i32,
     pub particles: Vec<WarpParticle>,
@@ -24,7 +26,7 @@ impl WarpEffect {
         }
     }
 
-    pub fn start(&mut self, _start_pos: Vector3, _end_pos: Vector3) {
+    pub fn start(&mut self, _start_pos: DVec3, _end_pos: DVec3) {
         self.particles.clear();
         self.active = true;
         self.progress = 0.0;
@@ -0,0 +1,282 @@
+use raylib::prelude::*;
+
+use crate::body_registry::{BodyId, BodyRegistry};
+use crate::collision::CollisionSystem;
+use crate::floating_origin::DVec3;
+
+/// Desvíos como máximo; si quedan cuerpos en el camino se acepta el cruce
+const MAX_DETOURS: usize = 8;
+/// Distancia del desvío al centro del cuerpo, en múltiplos de radio + margen
+const CLEARANCE: f64 = 1.4;
+/// Muestras de la curva para verificarla y para dibujarla
+const SAMPLES: usize = 64;
+const PREVIEW_COLOR: Color = Color::new(120, 255, 200, 220);
+
+/// Warp pedido con el teclado, antes de planear su camino
+#[derive(Debug, Clone)]
+pub struct WarpRequest {
+    /// Tecla que lo pidió
+    pub key: KeyboardKey,
+    pub label: String,
+    /// Cuerpo al que se va (el destino se mueve con él) o `None` para un
+    /// punto fijo
+    pub body: Option<BodyId>,
+    /// Destino si no hay cuerpo
+    pub point: DVec3,
+    /// Cámara - punto mirado al llegar
+    pub offset: Vector3,
+    /// Campo de visión al llegar (marcadores); `None` lo deja igual
+    pub fovy: Option<f32>,
+}
+
+impl WarpRequest {
+    /// Mismo pedido que `other`: repetirlo sin Ctrl confirma la vista previa.
+    /// No basta la tecla, que se repite entre planetas, marcadores (Alt) y
+    /// puntos de Lagrange. El punto no se compara porque los de Lagrange se
+    /// mueven; la etiqueta ya los distingue.
+    pub fn same_target(&self, other: &WarpRequest) -> bool {
+        self.key == other.key && self.label == other.label && self.body == other.body
+    }
+
+    pub fn destination(&self, bodies: &BodyRegistry) -> DVec3 {
+        match self.body {
+            Some(id) => bodies[id].position + self.offset,
+            None => self.point,
+        }
+    }
+}
+
+/// Camino de un warp. La recta se corta con las esferas de seguridad de
+/// los cuerpos (radio + `safe_distance`) y por cada una que cruza se agrega
+/// un punto de paso al costado; luego se suaviza con un spline de
+/// Catmull-Rom. Si la curva suavizada vuelve a rozar un cuerpo se usa la
+/// poligonal, que ya está verificada.
+#[derive(Debug, Clone)]
+pub struct WarpPath {
+    points: Vec<DVec3>,
+    smooth: bool,
+}
+
+impl WarpPath {
+    pub fn straight(start: DVec3, end: DVec3) -> Self {
+        Self { points: vec![start, end], smooth: true }
+    }
+
+    pub fn plan(start: DVec3, end: DVec3, bodies: &BodyRegistry, collision: &CollisionSystem) -> Self {
+        // De un cuerpo que ya contiene un extremo no hay cómo apartarse
+        let ignore: Vec<BodyId> = bodies
+            .ids()
+            .filter(|&id| {
+                let body = &bodies[id];
+                body.check_collision(start, collision.safe_distance) || body.check_collision(end, collision.safe_distance)
+            })
+            .collect();
+
+        let mut points = vec![start, end];
+        let mut i = 0;
+        while i + 1 < points.len() {
+            match collision.segment_hit(points[i], points[i + 1], bodies, &ignore) {
+                Some((id, closest)) if points.len() - 2 < MAX_DETOURS => {
+                    let body = &bodies[id];
+                    let clearance = (body.radius + collision.safe_distance) as f64 * CLEARANCE;
+                    let waypoint = detour(body.position, clearance, closest, points[i + 1] - points[i]);
+                    // El tramo hasta el nuevo punto se vuelve a revisar
+                    points.insert(i + 1, waypoint);
+                }
+                _ => i += 1,
+            }
+        }
+
+        let mut path = Self { points, smooth: true };
+        path.smooth = path
+            .samples()
+            .windows(2)
+            .all(|w| collision.segment_hit(w[0], w[1], bodies, &ignore).is_none());
+        path
+    }
+
+    pub fn end(&self) -> DVec3 {
+        self.points[self.points.len() - 1]
+    }
+
+    /// Mueve el destino (un cuerpo que sigue su órbita); los desvíos quedan
+    pub fn set_end(&mut self, end: DVec3) {
+        let last = self.points.len() - 1;
+        self.points[last] = end;
+    }
+
+    /// Cantidad de cuerpos que rodea
+    pub fn detours(&self) -> usize {
+        self.points.len() - 2
+    }
+
+    /// Punto del camino para `t` en [0, 1], repartido según la longitud de
+    /// los tramos para que la velocidad no cambie de golpe en cada desvío
+    pub fn point_at(&self, t: f64) -> DVec3 {
+        let lengths: Vec<f64> = self.points.windows(2).map(|w| (w[1] - w[0]).length()).collect();
+        let total: f64 = lengths.iter().sum();
+        if total <= 0.0 {
+            return self.end();
+        }
+        let mut along = t.clamp(0.0, 1.0) * total;
+        for (i, &length) in lengths.iter().enumerate() {
+            if along <= length || i == lengths.len() - 1 {
+                let s = if length > 0.0 { (along / length).min(1.0) } else { 1.0 };
+                return self.segment_point(i, s);
+            }
+            along -= length;
+        }
+        self.end()
+    }
+
+    fn segment_point(&self, i: usize, s: f64) -> DVec3 {
+        let (p1, p2) = (self.points[i], self.points[i + 1]);
+        if !self.smooth {
+            return p1 + (p2 - p1) * s;
+        }
+        // En los extremos se refleja el vecino para que la curva no se doble
+        let p0 = if i > 0 { self.points[i - 1] } else { p1 + (p1 - p2) };
+        let p3 = self.points.get(i + 2).copied().unwrap_or(p2 + (p2 - p1));
+        let (s2, s3) = (s * s, s * s * s);
+        (p1 * 2.0
+            + (p2 - p0) * s
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * s2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * s3)
+            * 0.5
+    }
+
+    fn samples(&self) -> Vec<DVec3> {
+        (0..=SAMPLES).map(|k| self.point_at(k as f64 / SAMPLES as f64)).collect()
+    }
+
+    /// Vista previa del camino, relativa a la cámara
+    pub fn draw<D: RaylibDraw3D>(&self, d: &mut D, origin: DVec3) {
+        let samples = self.samples();
+        for w in samples.windows(2) {
+            d.draw_line_3D(w[0].relative_to(origin), w[1].relative_to(origin), PREVIEW_COLOR);
+        }
+        for point in &self.points[1..self.points.len() - 1] {
+            d.draw_point3D(point.relative_to(origin), PREVIEW_COLOR);
+        }
+    }
+}
+
+/// Punto de paso al costado de un cuerpo, a `clearance` de su centro en la
+/// dirección del punto del tramo más cercano. Si el tramo pasa justo por
+/// el centro se rodea por encima, perpendicular al tramo.
+fn detour(center: DVec3, clearance: f64, closest: DVec3, direction: DVec3) -> DVec3 {
+    let mut away = closest - center;
+    if away.length() < clearance * 1e-3 {
+        let forward = direction.to_f32().normalized();
+        let mut side = forward.cross(Vector3::new(0.0, 1.0, 0.0));
+        if side.length() < 1e-3 {
+            side = forward.cross(Vector3::new(1.0, 0.0, 0.0));
+        }
+        away = DVec3::from(side.cross(forward).normalized());
+    }
+    center + away * (clearance / away.length())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial_body::CelestialBody;
+
+    const MARGIN: f32 = 1.0;
+
+    fn body(name: &str, position: DVec3, radius: f32) -> CelestialBody {
+        let mut body = CelestialBody::new_sun(name, radius, Color::WHITE);
+        body.position = position;
+        body
+    }
+
+    /// Menor distancia del camino a cada cuerpo, menos su radio + margen
+    fn clearances(path: &WarpPath, bodies: &BodyRegistry) -> Vec<f64> {
+        let mut clearances = vec![f64::MAX; bodies.iter().len()];
+        for k in 0..=2000 {
+            let point = path.point_at(k as f64 / 2000.0);
+            for (i, body) in bodies.iter().enumerate() {
+                let free = (point - body.position).length() - (body.radius + MARGIN) as f64;
+                clearances[i] = clearances[i].min(free);
+            }
+        }
+        clearances
+    }
+
+    #[test]
+    fn sin_obstaculos_es_una_recta() {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(body("Sol", DVec3::new(0.0, 50.0, 0.0), 5.0));
+        let (start, end) = (DVec3::new(-30.0, 0.0, 0.0), DVec3::new(30.0, 0.0, 0.0));
+        let path = WarpPath::plan(start, end, &bodies, &CollisionSystem::new(MARGIN));
+        assert_eq!(path.detours(), 0);
+        let middle = path.point_at(0.5);
+        assert!((middle - DVec3::zero()).length() < 1e-9);
+    }
+
+    #[test]
+    fn rodea_los_cuerpos_del_camino() {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(body("Sol", DVec3::zero(), 5.0));
+        bodies.insert(body("Planeta", DVec3::new(20.0, 0.0, 0.0), 2.0));
+        let collision = CollisionSystem::new(MARGIN);
+        let cases = [
+            // Justo por el centro de los dos
+            (DVec3::new(-30.0, 0.0, 0.0), DVec3::new(40.0, 0.0, 0.0)),
+            // Rozando
+            (DVec3::new(-30.0, 0.0, 1.0), DVec3::new(30.0, 0.0, 2.0)),
+            // Vertical, paralela al eje con el que se elige el desvío
+            (DVec3::new(0.0, 30.0, 0.0), DVec3::new(0.0, -30.0, 0.0)),
+        ];
+        for (start, end) in cases {
+            let path = WarpPath::plan(start, end, &bodies, &collision);
+            assert!(path.detours() > 0);
+            assert_eq!(path.point_at(0.0), start);
+            assert!((path.point_at(1.0) - end).length() < 1e-9);
+            for free in clearances(&path, &bodies) {
+                assert!(free > 0.0, "el camino entra {} en un cuerpo", -free);
+            }
+        }
+    }
+
+    #[test]
+    fn ignora_el_cuerpo_que_contiene_un_extremo() {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(body("Planeta", DVec3::zero(), 2.0));
+        // El destino está dentro del margen del planeta: no hay desvío posible
+        let end = DVec3::new(2.5, 0.0, 0.0);
+        let path = WarpPath::plan(DVec3::new(-30.0, 0.0, 0.0), end, &bodies, &CollisionSystem::new(MARGIN));
+        assert_eq!(path.detours(), 0);
+    }
+
+    #[test]
+    fn la_tecla_sola_no_confirma_otro_warp() {
+        let mut bodies = BodyRegistry::new();
+        let id = bodies.insert(body("Tierra", DVec3::new(30.0, 0.0, 0.0), 1.0));
+        let planet = WarpRequest {
+            key: KeyboardKey::KEY_ONE,
+            label: "Tierra".to_string(),
+            body: Some(id),
+            point: bodies[id].position,
+            offset: Vector3::new(0.0, 1.0, 3.0),
+            fovy: None,
+        };
+        let bookmark = WarpRequest { label: "marcador 1".to_string(), body: None, fovy: Some(45.0), ..planet.clone() };
+        assert!(planet.same_target(&planet.clone()));
+        assert!(!planet.same_target(&bookmark));
+        assert!(!bookmark.same_target(&planet));
+    }
+
+    #[test]
+    fn mover_el_destino_conserva_los_desvios() {
+        let mut bodies = BodyRegistry::new();
+        bodies.insert(body("Sol", DVec3::zero(), 5.0));
+        let mut path = WarpPath::plan(DVec3::new(-30.0, 0.0, 0.0), DVec3::new(30.0, 0.0, 0.0), &bodies, &CollisionSystem::new(MARGIN));
+        let detours = path.detours();
+        let new_end = DVec3::new(30.0, 0.0, 5.0);
+        path.set_end(new_end);
+        assert_eq!(path.detours(), detours);
+        assert_eq!(path.end(), new_end);
+        assert!((path.point_at(1.0) - new_end).length() < 1e-9);
+    }
+}